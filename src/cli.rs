@@ -0,0 +1,276 @@
+pub mod cli {
+    use crate::gen_engine::gen_engine::RNG_BACKEND;
+    use crate::generator::generator::Generator;
+    use crate::secure_mem::secure_mem::wipe_secret;
+    use serde::Serialize;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    pub const USAGE: &str = "\
+Usage: passgen-console-linuxwin [OPTIONS]
+
+Without options the interactive interface is started. Options that are not given come
+from the PASSGEN_* variables, then the config file, except the length and the character
+classes the interface saves there: those keep the defaults below.
+
+Options:
+  -l, --length <N>        password length (default 8)
+  -c, --count <N>         number of passwords to print (default 1)
+      --lower             include lowercase letters
+      --upper             include capital letters
+      --digits            include numbers
+      --symbols           include special symbols
+      --strong            strong & usability password (default when no classes are given)
+  -f, --format <F>        output format: plain, json or jsonl (default plain)
+  -t, --template <TEXT>   output template, e.g. 'DB_PASSWORD=\"{password}\"'
+      --label <TEXT>      value of {label} in the template and PASSGEN_LABEL in the hook
+      --hook <CMD>        command run after each password, which is written to its stdin
+      --pick              choose the password in the interface and print it to stdout
+                          (exits with 130 when cancelled)
+  -h, --help              print this help
+  -V, --version           print version";
+
+    pub struct CliOptions {
+        pub length: Option<String>,
+        pub count: u32,
+        pub letters: bool,
+        pub u_letters: bool,
+        pub numbs: bool,
+        pub spec_symbs: bool,
+        pub convenience_criterion: bool,
+        pub format: String,
+        pub label: Option<String>,
+        pub hook: Option<String>,
+        pub template: Option<String>,
+    }
+
+    #[derive(Serialize)]
+    struct Policy {
+        length: u32,
+        lowercase: bool,
+        uppercase: bool,
+        digits: bool,
+        symbols: bool,
+        strong: bool,
+    }
+
+    #[derive(Serialize)]
+    struct GenerationRecord<'a> {
+        password: &'a str,
+        policy: &'a Policy,
+        charset_size: usize,
+        entropy_bits: f64,
+        rng: &'static str,
+        timestamp: String,
+    }
+
+    pub enum CliCommand {
+        Tui,
+        Generate(CliOptions),
+        Pick(CliOptions),
+        Help,
+        Version,
+    }
+
+    pub fn parse_args(args: &[String]) -> Result<CliCommand, String> {
+        if args.is_empty() {
+            return Ok(CliCommand::Tui);
+        }
+
+        let mut options = CliOptions {
+            length: None,
+            count: 1,
+            letters: false,
+            u_letters: false,
+            numbs: false,
+            spec_symbs: false,
+            convenience_criterion: false,
+            format: "plain".to_string(),
+            label: None,
+            hook: None,
+            template: None,
+        };
+        let mut pick = false;
+        // Options of the printed output, which `--pick` does not produce
+        let mut output_option: Option<&str> = None;
+        let mut args_iter = args.iter();
+        while let Some(arg) = args_iter.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(CliCommand::Help),
+                "--pick" => pick = true,
+                "-V" | "--version" => return Ok(CliCommand::Version),
+                "-l" | "--length" => {
+                    options.length = Some(take_value(arg, args_iter.next())?);
+                }
+                "-c" | "--count" => {
+                    output_option = Some(arg);
+                    let value = take_value(arg, args_iter.next())?;
+                    options.count = match value.parse::<u32>() {
+                        Ok(count) if count > 0 => count,
+                        _ => return Err(format!("invalid value '{}' for '{}'", value, arg)),
+                    };
+                }
+                "--label" => options.label = Some(take_value(arg, args_iter.next())?),
+                "--hook" => options.hook = Some(take_value(arg, args_iter.next())?),
+                "-t" | "--template" => options.template = Some(take_value(arg, args_iter.next())?),
+                "--lower" => options.letters = true,
+                "--upper" => options.u_letters = true,
+                "--digits" => options.numbs = true,
+                "--symbols" => options.spec_symbs = true,
+                "--strong" => options.convenience_criterion = true,
+                "-f" | "--format" => {
+                    output_option = Some(arg);
+                    let value = take_value(arg, args_iter.next())?;
+                    if !["plain", "json", "jsonl"].contains(&value.as_str()) {
+                        return Err(format!("invalid value '{}' for '{}'", value, arg));
+                    }
+                    options.format = value;
+                }
+                _ => return Err(format!("unknown argument '{}'", arg)),
+            }
+        }
+        if pick {
+            if let Some(arg) = output_option {
+                return Err(format!("'{}' can not be used with '--pick'", arg));
+            }
+            Ok(CliCommand::Pick(options))
+        } else {
+            Ok(CliCommand::Generate(options))
+        }
+    }
+
+    impl CliOptions {
+        /// Uses the given options as the starting state of the interface.
+        pub fn apply_to(&self, generator: &mut Generator) {
+            if let Some(label) = &self.label {
+                generator.label = label.clone();
+            }
+            if let Some(hook) = &self.hook {
+                generator.hook = Some(hook.clone());
+            }
+            if let Some(template) = &self.template {
+                generator.template = Some(template.clone());
+            }
+            if let Some(length) = &self.length {
+                generator.pwd_len = length.clone();
+                generator.cursor_to_end();
+            }
+            if self.letters
+                || self.u_letters
+                || self.numbs
+                || self.spec_symbs
+                || self.convenience_criterion
+            {
+                generator.letters = self.letters;
+                generator.u_letters = self.u_letters;
+                generator.numbs = self.numbs;
+                generator.spec_symbs = self.spec_symbs;
+                generator.convenience_criterion = self.convenience_criterion;
+            }
+            // The administrator policy stays the last layer
+            generator.policy.clone().apply_to(generator);
+        }
+    }
+
+    fn take_value(arg: &str, value: Option<&String>) -> Result<String, String> {
+        match value {
+            Some(value) => Ok(value.clone()),
+            None => Err(format!("a value is required for '{}'", arg)),
+        }
+    }
+
+    /// Prints `count` passwords to stdout without entering the interactive interface.
+    pub fn run_cli(options: CliOptions, mut generator: Generator) -> Result<(), String> {
+        // A script gets an error rather than passwords of another length than it asked for
+        if let (Some(length), Some(min_length)) = (&options.length, generator.policy.min_length) {
+            if length
+                .parse::<u32>()
+                .is_ok_and(|length| length < min_length)
+            {
+                return Err(format!(
+                    "length {} is below the administrator policy minimum of {}",
+                    length, min_length
+                ));
+            }
+        }
+        options.apply_to(&mut generator);
+
+        let policy = Policy {
+            length: generator.pwd_len.parse().unwrap_or(0),
+            lowercase: generator.letters,
+            uppercase: generator.u_letters,
+            digits: generator.numbs,
+            symbols: generator.spec_symbs,
+            strong: generator.is_convenient_mode(),
+        };
+
+        for i in 0..options.count {
+            let mut pwd = generator.generate_valid_pwd().map_err(|err| err.0)?;
+            if let Err(err) = generator.check_min_entropy(generator.entropy_bits(policy.length)) {
+                wipe_secret(&mut pwd);
+                return Err(err.0);
+            }
+            if options.format == "plain" {
+                let mut text = generator.render_template(&pwd);
+                println!("{}", text);
+                wipe_secret(&mut text);
+            } else {
+                let record = GenerationRecord {
+                    password: &pwd,
+                    policy: &policy,
+                    charset_size: generator.charset_size(),
+                    entropy_bits: (generator.entropy_bits(policy.length) * 100.0).round() / 100.0,
+                    rng: RNG_BACKEND,
+                    timestamp: utc_timestamp(),
+                };
+                let mut line = serde_json::to_string(&record).map_err(|err| err.to_string())?;
+                if options.format == "json" {
+                    if i == 0 {
+                        println!("[");
+                    }
+                    let sep = if i + 1 < options.count { "," } else { "" };
+                    println!("  {}{}", line, sep);
+                } else {
+                    println!("{}", line);
+                }
+                wipe_secret(&mut line);
+            }
+            let hook_res = generator.run_hook(&pwd);
+            wipe_secret(&mut pwd);
+            hook_res.map_err(|err| err.0)?;
+        }
+        if options.format == "json" {
+            println!("]");
+        }
+        Ok(())
+    }
+
+    /// Current UTC time in RFC 3339 format.
+    pub fn utc_timestamp() -> String {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let (days, day_secs) = (secs / 86400, secs % 86400);
+
+        // civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+        let z = days as i64 + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year,
+            month,
+            day,
+            day_secs / 3600,
+            day_secs % 3600 / 60,
+            day_secs % 60
+        )
+    }
+}
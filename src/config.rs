@@ -0,0 +1,347 @@
+pub mod config {
+    use crate::generator::generator::{Generator, CLIPBOARD_TARGETS};
+    use crate::keymap::keymap::{Keymap, KEYMAPS};
+    use crate::theme::theme::THEMES;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+    use std::env;
+    use std::fs;
+    use std::io::ErrorKind;
+    use std::path::PathBuf;
+
+    const CONFIG_DIR: &str = "passgen-console-linuxwin";
+    const CONFIG_FILE: &str = "config.toml";
+
+    /// User settings persisted between launches. Missing keys keep the defaults,
+    /// unknown keys are reported as config errors.
+    #[derive(Default, Deserialize, PartialEq, Serialize)]
+    #[serde(default, deny_unknown_fields)]
+    pub struct Config {
+        pub length: Option<u32>,
+        pub lowercase: Option<bool>,
+        pub uppercase: Option<bool>,
+        pub digits: Option<bool>,
+        pub symbols: Option<bool>,
+        pub strong: Option<bool>,
+        pub lang: Option<String>,
+        pub clipboard: Option<String>,
+        pub auto_copy: Option<bool>,
+        pub idle_timeout: Option<u64>,
+        pub mouse: Option<bool>,
+        pub masked: Option<bool>,
+        pub theme: Option<String>,
+        pub keymap: Option<String>,
+        pub confirm_quit: Option<bool>,
+        pub label: Option<String>,
+        pub hook: Option<String>,
+        pub template: Option<String>,
+        /// Keys of actions, overriding the keymap: `copy = "y"`, `quit = "ctrl+q, q"`.
+        #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+        pub keys: BTreeMap<String, String>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        pub presets: Vec<Preset>,
+    }
+
+    /// A named rule set selectable from the presets popup.
+    #[derive(Clone, Deserialize, PartialEq, Serialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct Preset {
+        pub name: String,
+        pub length: u32,
+        #[serde(default)]
+        pub lowercase: bool,
+        #[serde(default)]
+        pub uppercase: bool,
+        #[serde(default)]
+        pub digits: bool,
+        #[serde(default)]
+        pub symbols: bool,
+        #[serde(default)]
+        pub strong: bool,
+    }
+
+    impl Config {
+        /// `$XDG_CONFIG_HOME/passgen-console-linuxwin/config.toml`, falling back to
+        /// `~/.config` on unix and `%APPDATA%` on windows.
+        pub fn path() -> Option<PathBuf> {
+            let base = match env::var_os("XDG_CONFIG_HOME") {
+                Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+                _ => {
+                    if cfg!(windows) {
+                        PathBuf::from(env::var_os("APPDATA")?)
+                    } else {
+                        PathBuf::from(env::var_os("HOME")?).join(".config")
+                    }
+                }
+            };
+            Some(base.join(CONFIG_DIR).join(CONFIG_FILE))
+        }
+
+        pub fn load() -> Result<Config, String> {
+            let Some(path) = Config::path() else {
+                return Ok(Config::default());
+            };
+            let config: Config = match fs::read_to_string(&path) {
+                Ok(text) => toml::from_str(&text)
+                    .map_err(|err| format!("{}: {}", path.display(), err.message()))?,
+                Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+                Err(err) => return Err(format!("{}: {}", path.display(), err)),
+            };
+            if let Some(keymap) = &config.keymap {
+                if !KEYMAPS.contains(&keymap.as_str()) {
+                    return Err(format!("{}: unknown keymap '{}'", path.display(), keymap));
+                }
+            }
+            Keymap::build("default", &config.keys)
+                .map_err(|err| format!("{}: {}", path.display(), err))?;
+            Ok(config)
+        }
+
+        pub fn save(&self) -> Result<(), String> {
+            let Some(path) = Config::path() else {
+                return Err("no config directory found".to_string());
+            };
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+            }
+            let text = toml::to_string(self).map_err(|err| err.to_string())?;
+            fs::write(&path, text).map_err(|err| format!("{}: {}", path.display(), err))
+        }
+
+        /// Partial config from the `PASSGEN_*` variables, layered above the file and below CLI flags.
+        /// Invalid variables are left out and returned as errors.
+        pub fn from_env() -> (Config, Vec<String>) {
+            let mut errors = Vec::new();
+            let config = Config {
+                length: env_value(&mut errors, "PASSGEN_LENGTH", |v| v.parse().ok()),
+                lowercase: env_value(&mut errors, "PASSGEN_LOWERCASE", parse_bool),
+                uppercase: env_value(&mut errors, "PASSGEN_UPPERCASE", parse_bool),
+                digits: env_value(&mut errors, "PASSGEN_DIGITS", parse_bool),
+                symbols: env_value(&mut errors, "PASSGEN_SYMBOLS", parse_bool),
+                strong: env_value(&mut errors, "PASSGEN_STRONG", parse_bool),
+                lang: env_value(&mut errors, "PASSGEN_LANG", |v| {
+                    ["en", "ru"].contains(&v).then(|| v.to_string())
+                }),
+                clipboard: env_value(&mut errors, "PASSGEN_CLIPBOARD", |v| {
+                    CLIPBOARD_TARGETS.contains(&v).then(|| v.to_string())
+                }),
+                auto_copy: env_value(&mut errors, "PASSGEN_AUTO_COPY", parse_bool),
+                idle_timeout: env_value(&mut errors, "PASSGEN_IDLE_TIMEOUT", |v| v.parse().ok()),
+                mouse: env_value(&mut errors, "PASSGEN_MOUSE", parse_bool),
+                masked: env_value(&mut errors, "PASSGEN_MASKED", parse_bool),
+                theme: env_value(&mut errors, "PASSGEN_THEME", |v| {
+                    THEMES.contains(&v).then(|| v.to_string())
+                }),
+                keymap: env_value(&mut errors, "PASSGEN_KEYMAP", |v| {
+                    KEYMAPS.contains(&v).then(|| v.to_string())
+                }),
+                confirm_quit: env_value(&mut errors, "PASSGEN_CONFIRM_QUIT", parse_bool),
+                label: env_value(&mut errors, "PASSGEN_LABEL", |v| Some(v.to_string())),
+                hook: env_value(&mut errors, "PASSGEN_HOOK", |v| Some(v.to_string())),
+                template: env_value(&mut errors, "PASSGEN_TEMPLATE", |v| Some(v.to_string())),
+                keys: BTreeMap::new(),
+                presets: Vec::new(),
+            };
+            (config, errors)
+        }
+
+        /// Keeps the file values of the settings overridden by the environment,
+        /// so that the overrides are not persisted on save.
+        pub fn without_overrides(mut self, file: &Config, overrides: &Config) -> Config {
+            if overrides.length.is_some() {
+                self.length = file.length;
+            }
+            if overrides.lowercase.is_some() {
+                self.lowercase = file.lowercase;
+            }
+            if overrides.uppercase.is_some() {
+                self.uppercase = file.uppercase;
+            }
+            if overrides.digits.is_some() {
+                self.digits = file.digits;
+            }
+            if overrides.symbols.is_some() {
+                self.symbols = file.symbols;
+            }
+            if overrides.strong.is_some() {
+                self.strong = file.strong;
+            }
+            if overrides.lang.is_some() {
+                self.lang = file.lang.clone();
+            }
+            if overrides.clipboard.is_some() {
+                self.clipboard = file.clipboard.clone();
+            }
+            if overrides.auto_copy.is_some() {
+                self.auto_copy = file.auto_copy;
+            }
+            if overrides.idle_timeout.is_some() {
+                self.idle_timeout = file.idle_timeout;
+            }
+            if overrides.mouse.is_some() {
+                self.mouse = file.mouse;
+            }
+            if overrides.masked.is_some() {
+                self.masked = file.masked;
+            }
+            if overrides.theme.is_some() {
+                self.theme = file.theme.clone();
+            }
+            if overrides.keymap.is_some() {
+                self.keymap = file.keymap.clone();
+            }
+            if overrides.confirm_quit.is_some() {
+                self.confirm_quit = file.confirm_quit;
+            }
+            if overrides.label.is_some() {
+                self.label = file.label.clone();
+            }
+            if overrides.hook.is_some() {
+                self.hook = file.hook.clone();
+            }
+            if overrides.template.is_some() {
+                self.template = file.template.clone();
+            }
+            self
+        }
+
+        /// Drops the length and the character classes, which the interface saves on exit.
+        pub fn without_rules(self) -> Config {
+            Config {
+                length: None,
+                lowercase: None,
+                uppercase: None,
+                digits: None,
+                symbols: None,
+                strong: None,
+                ..self
+            }
+        }
+
+        pub fn from_generator(generator: &Generator) -> Config {
+            Config {
+                length: generator.pwd_len.parse().ok(),
+                lowercase: Some(generator.letters),
+                uppercase: Some(generator.u_letters),
+                digits: Some(generator.numbs),
+                symbols: Some(generator.spec_symbs),
+                strong: Some(generator.convenience_criterion),
+                lang: Some(generator.lang.clone()),
+                clipboard: Some(generator.clipboard_target.clone()),
+                auto_copy: Some(generator.auto_copy),
+                idle_timeout: Some(generator.idle_timeout_secs),
+                mouse: Some(generator.mouse),
+                masked: Some(generator.pwd_masked),
+                theme: generator.theme.clone(),
+                keymap: Some(generator.keymap_name.clone()),
+                confirm_quit: Some(generator.confirm_quit),
+                label: (!generator.label.is_empty()).then(|| generator.label.clone()),
+                hook: generator.hook.clone(),
+                template: generator.template.clone(),
+                keys: generator.key_bindings.clone(),
+                presets: generator.presets.clone(),
+            }
+        }
+
+        pub fn apply_to(&self, generator: &mut Generator) {
+            if let Some(length) = self.length {
+                generator.pwd_len = length.to_string();
+                generator.cursor_to_end();
+            }
+            if let Some(lowercase) = self.lowercase {
+                generator.letters = lowercase;
+            }
+            if let Some(uppercase) = self.uppercase {
+                generator.u_letters = uppercase;
+            }
+            if let Some(digits) = self.digits {
+                generator.numbs = digits;
+            }
+            if let Some(symbols) = self.symbols {
+                generator.spec_symbs = symbols;
+            }
+            if let Some(strong) = self.strong {
+                generator.convenience_criterion = strong;
+            }
+            if let Some(lang) = &self.lang {
+                if lang == "en" || lang == "ru" {
+                    generator.lang = lang.clone();
+                }
+            }
+            if let Some(clipboard) = &self.clipboard {
+                if CLIPBOARD_TARGETS.contains(&clipboard.as_str()) {
+                    generator.clipboard_target = clipboard.clone();
+                }
+            }
+            if let Some(auto_copy) = self.auto_copy {
+                generator.auto_copy = auto_copy;
+            }
+            if let Some(idle_timeout) = self.idle_timeout {
+                generator.idle_timeout_secs = idle_timeout;
+            }
+            if let Some(mouse) = self.mouse {
+                generator.mouse = mouse;
+            }
+            if let Some(masked) = self.masked {
+                generator.pwd_masked = masked;
+            }
+            if let Some(theme) = &self.theme {
+                if THEMES.contains(&theme.as_str()) {
+                    generator.theme = Some(theme.clone());
+                }
+            }
+            if let Some(keymap) = &self.keymap {
+                if KEYMAPS.contains(&keymap.as_str()) {
+                    generator.keymap_name = keymap.clone();
+                }
+            }
+            if !self.keys.is_empty() {
+                generator.key_bindings = self.keys.clone();
+            }
+            if let Ok(keymap) = Keymap::build(&generator.keymap_name, &generator.key_bindings) {
+                generator.keymap = keymap;
+            }
+            if let Some(confirm_quit) = self.confirm_quit {
+                generator.confirm_quit = confirm_quit;
+            }
+            if let Some(label) = &self.label {
+                generator.label = label.clone();
+            }
+            if let Some(hook) = &self.hook {
+                generator.hook = Some(hook.clone());
+            }
+            if let Some(template) = &self.template {
+                generator.template = Some(template.clone());
+            }
+            if !self.presets.is_empty() {
+                generator.presets = self.presets.clone();
+            }
+        }
+    }
+
+    /// An invalid variable is reported and skipped, the others still apply.
+    fn env_value<T>(
+        errors: &mut Vec<String>,
+        name: &str,
+        parse: impl Fn(&str) -> Option<T>,
+    ) -> Option<T> {
+        match env::var(name) {
+            Ok(value) if !value.trim().is_empty() => {
+                let parsed = parse(value.trim());
+                if parsed.is_none() {
+                    errors.push(format!("invalid value '{}' of {}", value, name));
+                }
+                parsed
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_bool(value: &str) -> Option<bool> {
+        match value.to_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Some(true),
+            "0" | "false" | "no" | "off" => Some(false),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+pub mod escape {
+    /// Destinations the password can be escaped for, in the order of the popup rows.
+    pub const ESCAPE_VIEWS: [&str; 6] = ["shell", "json", "url", "yaml", "xml", "sql"];
+
+    pub fn escape_for(view: &str, pwd: &str) -> String {
+        match view {
+            "shell" => format!("'{}'", pwd.replace('\'', "'\\''")),
+            "json" => serde_json::to_string(pwd).unwrap_or_default(),
+            "url" => url_encode(pwd),
+            "yaml" | "sql" => format!("'{}'", pwd.replace('\'', "''")),
+            "xml" => xml_escape(pwd),
+            _ => pwd.to_string(),
+        }
+    }
+
+    pub fn view_title(view: &str) -> &'static str {
+        match view {
+            "shell" => "POSIX shell",
+            "json" => "JSON",
+            "url" => "URL",
+            "yaml" => "YAML",
+            "xml" => "XML",
+            "sql" => "SQL",
+            _ => "",
+        }
+    }
+
+    fn url_encode(text: &str) -> String {
+        let mut encoded = String::new();
+        for byte in text.bytes() {
+            if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+                encoded.push(byte as char);
+            } else {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        }
+        encoded
+    }
+
+    fn xml_escape(text: &str) -> String {
+        let mut escaped = String::new();
+        for ch in text.chars() {
+            match ch {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                _ => escaped.push(ch),
+            }
+        }
+        escaped
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const HOSTILE: &str = "'\"$&([<>";
+
+        #[test]
+        fn shell_view_single_quotes() {
+            assert_eq!(escape_for("shell", HOSTILE), r#"''\''"$&([<>'"#);
+        }
+
+        #[test]
+        fn json_view_is_a_string_literal() {
+            assert_eq!(escape_for("json", HOSTILE), r#""'\"$&([<>""#);
+        }
+
+        #[test]
+        fn url_view_percent_encodes_reserved_bytes() {
+            assert_eq!(escape_for("url", HOSTILE), "%27%22%24%26%28%5B%3C%3E");
+            assert_eq!(escape_for("url", "a-Z._~9"), "a-Z._~9");
+        }
+
+        #[test]
+        fn yaml_and_sql_views_double_single_quotes() {
+            assert_eq!(escape_for("yaml", HOSTILE), r#"'''"$&([<>'"#);
+            assert_eq!(escape_for("sql", HOSTILE), r#"'''"$&([<>'"#);
+        }
+
+        #[test]
+        fn xml_view_uses_entities() {
+            assert_eq!(escape_for("xml", HOSTILE), "&apos;&quot;$&amp;([&lt;&gt;");
+        }
+
+        #[test]
+        fn every_view_has_a_title() {
+            for view in ESCAPE_VIEWS {
+                assert!(!view_title(view).is_empty());
+            }
+        }
+    }
+}
@@ -1,10 +1,13 @@
 pub mod gen_engine {
     use crate::generator::generator::Generator;
+    use crate::secure_mem::secure_mem::lock_buffer;
     use rand::Rng;
     use rand_hc::Hc128Rng;
     use rand_isaac::Isaac64Rng;
     use rand_seeder::rand_core::SeedableRng;
 
+    pub const RNG_BACKEND: &str = "Isaac64Rng-seeded Hc128Rng";
+
     const LETTERS_CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
     const U_LETTERS_CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
     const NUMBERS_CHARSET: &[u8] = b"0123456789";
@@ -15,15 +18,66 @@ pub mod gen_engine {
     const CONVENIENT_LETTERS_CHARSET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZabcdefghjkmnpqrstuvwxyz"; // set without ambiguous and inconvenient letters with numbers / набор без двоякочитаемых и неудобных букв с цифрами
 
     impl Generator {
-        pub fn generate_pass(&mut self) -> String {
-            let mut isaac_seeder = Isaac64Rng::from_entropy();
-            let mut rng = Hc128Rng::from_rng(&mut isaac_seeder).unwrap();
-            let mut pass_assembly: Vec<&[u8]> = Vec::new();
+        pub fn generate_pass(&mut self) -> Result<String, (String, String)> {
+            let mut rng = secure_rng()?;
+            if self.is_convenient_mode() {
+                self.convenience_criterion = true;
+            }
+            let pass_charset: Vec<u8> = self.pass_charset();
+            let pass_processing_len: u32 = match self.pwd_len.parse::<u32>() {
+                Ok(len) if len >= 2 => len,
+                _ => {
+                    return Err((
+                        format!("Invalid password length '{}'!", self.pwd_len),
+                        format!("Недопустимая длина пароля '{}'!", self.pwd_len),
+                    ))
+                }
+            };
+            // Allocated once and locked before the first symbol, so that no
+            // reallocation leaves a copy of the password in swappable memory
+            let mut pass_candidate_vec: Vec<u8> = Vec::with_capacity(pass_processing_len as usize);
+            lock_buffer(&pass_candidate_vec);
+
+            if self.convenience_criterion {
+                let letters_charset: Vec<u8> = CONVENIENT_LETTERS_CHARSET.to_vec();
+                let simp_symb_charset: Vec<u8> = SIMP_SYMB_CHARSET.to_vec();
 
-            if self.convenience_criterion
+                // gen first pass symbol from all letters
+                pass_candidate_vec.push(letters_charset[rng.gen_range(0..letters_charset.len())]);
+
+                // gen main pass body
+                for _ in 0..(pass_processing_len - 2) {
+                    pass_candidate_vec.push(pass_charset[rng.gen_range(0..pass_charset.len())]);
+                }
+
+                // gen last pass symbol from simple symbols
+                pass_candidate_vec
+                    .push(simp_symb_charset[rng.gen_range(0..simp_symb_charset.len())]);
+            } else {
+                for _ in 0..pass_processing_len {
+                    pass_candidate_vec.push(pass_charset[rng.gen_range(0..pass_charset.len())]);
+                }
+            }
+
+            String::from_utf8(pass_candidate_vec).map_err(|_err| {
+                (
+                    "Generated password is not valid UTF-8!".to_string(),
+                    "Сгенерированный пароль не является корректным UTF-8!".to_string(),
+                )
+            })
+        }
+
+        /// The strong & usability charset is used when it is on or no class is chosen.
+        pub fn is_convenient_mode(&self) -> bool {
+            self.convenience_criterion
                 || (!self.letters && !self.u_letters && !self.numbs && !self.spec_symbs)
-            {
-                self.convenience_criterion = true;
+        }
+
+        /// Symbols of the password body for the current rules.
+        pub fn pass_charset(&self) -> Vec<u8> {
+            let mut pass_assembly: Vec<&[u8]> = Vec::new();
+
+            if self.is_convenient_mode() {
                 pass_assembly.push(CONVENIENT_CHARSET);
             } else {
                 if self.letters {
@@ -40,42 +94,39 @@ pub mod gen_engine {
                 }
             }
 
-            let pass_charset: Vec<u8> = pass_assembly.into_iter().flatten().cloned().collect();
-            let mut pass_candidate_vec: Vec<u8> = Vec::new();
-            let pass_processing_len: u32 = self.pwd_len.parse::<u32>().unwrap();
-
-            if self.convenience_criterion {
-                let letters_charset: Vec<u8> =
-                    CONVENIENT_LETTERS_CHARSET.into_iter().cloned().collect();
-                let simp_symb_charset: Vec<u8> = SIMP_SYMB_CHARSET.into_iter().cloned().collect();
+            pass_assembly.into_iter().flatten().cloned().collect()
+        }
 
-                // gen first pass symbol from all letters
-                pass_candidate_vec.push(letters_charset[rng.gen_range(0..letters_charset.len())]);
+        /// Number of distinct symbols a password may contain with the current rules.
+        pub fn charset_size(&self) -> usize {
+            if self.is_convenient_mode() {
+                CONVENIENT_CHARSET.len() + SIMP_SYMB_CHARSET.len()
+            } else {
+                self.pass_charset().len()
+            }
+        }
 
-                // gen main pass body
-                for _ in 0..(pass_processing_len - 2) {
-                    pass_candidate_vec.push(pass_charset[rng.gen_range(0..pass_charset.len())]);
+        /// Upper estimate of the password entropy in bits for the given length.
+        pub fn entropy_bits(&self, pwd_len: u32) -> f64 {
+            if self.is_convenient_mode() {
+                if pwd_len < 2 {
+                    return 0.0;
                 }
-
-                // gen last pass symbol from simple symbols
-                pass_candidate_vec
-                    .push(simp_symb_charset[rng.gen_range(0..simp_symb_charset.len())]);
-
-                String::from_utf8(pass_candidate_vec).unwrap()
+                (CONVENIENT_LETTERS_CHARSET.len() as f64).log2()
+                    + (pwd_len - 2) as f64 * (CONVENIENT_CHARSET.len() as f64).log2()
+                    + (SIMP_SYMB_CHARSET.len() as f64).log2()
             } else {
-                (0..pass_processing_len)
-                    .map(|_| pass_charset[rng.gen_range(0..pass_charset.len())] as char)
-                    .collect()
+                pwd_len as f64 * (self.pass_charset().len() as f64).log2()
             }
         }
 
-        pub fn is_valid_pwd_by_consist(&self, pass: String) -> bool {
-            let pwd_in_bytes = pass.clone().into_bytes();
+        pub fn is_valid_pwd_by_consist(&self, pass: &str) -> bool {
+            let pwd_in_bytes = pass.as_bytes();
 
             let check_to_available_for = |symbols: &[u8]| -> bool {
                 let mut res = false;
-                for ch in &pwd_in_bytes {
-                    if symbols.contains(&ch) {
+                for ch in pwd_in_bytes {
+                    if symbols.contains(ch) {
                         res = true;
                         break;
                     }
@@ -84,27 +135,38 @@ pub mod gen_engine {
             };
 
             // compliance check
-            if self.letters || self.convenience_criterion {
-                if !check_to_available_for(LETTERS_CHARSET) {
-                    return false;
-                }
+            if (self.letters || self.convenience_criterion)
+                && !check_to_available_for(LETTERS_CHARSET)
+            {
+                return false;
             }
-            if self.u_letters || self.convenience_criterion {
-                if !check_to_available_for(U_LETTERS_CHARSET) {
-                    return false;
-                }
+            if (self.u_letters || self.convenience_criterion)
+                && !check_to_available_for(U_LETTERS_CHARSET)
+            {
+                return false;
             }
-            if self.numbs || self.convenience_criterion {
-                if !check_to_available_for(NUMBERS_CHARSET) {
-                    return false;
-                }
+            if (self.numbs || self.convenience_criterion)
+                && !check_to_available_for(NUMBERS_CHARSET)
+            {
+                return false;
             }
-            if self.spec_symbs || self.convenience_criterion {
-                if !check_to_available_for(SPEC_SYMB_CHARSET) {
-                    return false;
-                }
+            if (self.spec_symbs || self.convenience_criterion)
+                && !check_to_available_for(SPEC_SYMB_CHARSET)
+            {
+                return false;
             }
             true
         }
     }
+
+    /// Hc128Rng seeded from an Isaac64Rng drawn from OS entropy.
+    pub fn secure_rng() -> Result<Hc128Rng, (String, String)> {
+        let mut isaac_seeder = Isaac64Rng::from_entropy();
+        Hc128Rng::from_rng(&mut isaac_seeder).map_err(|_err| {
+            (
+                "Failed to seed the random generator!".to_string(),
+                "Не удалось инициализировать генератор случайных чисел!".to_string(),
+            )
+        })
+    }
 }
@@ -1,6 +1,19 @@
 pub mod generator {
+    use crate::config::config::Preset;
+    use crate::escape::escape::{escape_for, ESCAPE_VIEWS};
+    use crate::history::history::HistoryEntry;
+    use crate::keymap::keymap::Keymap;
+    use crate::modes::modes::{
+        ID_KINDS, PIN_RANGE, SEPARATORS, TOKEN_ENCODINGS, TOKEN_RANGE, WORDS_RANGE,
+    };
+    use crate::policy::policy::AdminPolicy;
+    use crate::secure_mem::secure_mem::{lock_secret, wipe_secret};
     use arboard::Clipboard;
+    use ratatui::layout::Rect;
+    use std::collections::BTreeMap;
+    use std::io::Write;
     use std::process::{Command, Stdio};
+    use std::time::Instant;
 
     const CIRCUITED_FIELDS: [&str; 6] = [
         "pwd_len",
@@ -11,6 +24,22 @@ pub mod generator {
         "convenience_criterion",
     ];
 
+    /// Modes of the interface, switched with Ctrl+Left/Right.
+    pub const TABS: [&str; 6] = [
+        "password",
+        "passphrase",
+        "pin",
+        "token",
+        "identifier",
+        "analyze",
+    ];
+
+    /// X11 selections the password may be copied to ("none" disables copying).
+    pub const CLIPBOARD_TARGETS: [&str; 4] = ["clipboard", "primary", "both", "none"];
+
+    /// Password lengths picked with Alt+1 .. Alt+5.
+    pub const COMMON_LENGTHS: [u32; 5] = [12, 16, 20, 32, 64];
+
     pub struct Generator {
         pub letters: bool,
         pub u_letters: bool,
@@ -22,13 +51,66 @@ pub mod generator {
         pub pwd_len: String,
         pub min_pwd_len: u32,
         pub max_pwd_len: u32,
+        pub policy: AdminPolicy,
+        pub tab: String,
+        pub words: String,
+        pub separator: String,
+        pub capitalize: bool,
+        pub add_number: bool,
+        pub pin_len: String,
+        pub pin_no_patterns: bool,
+        pub token_bytes: String,
+        pub token_encoding: String,
+        pub id_kind: String,
+        pub analyze_input: String,
         pub pwd: String,
+        pub pwd_copied: bool,
+        pub clipboard_target: String,
+        pub auto_copy: bool,
+        pub focus_lost: bool,
+        pub pick_mode: bool,
+        pub presets: Vec<Preset>,
+        pub active_preset: String,
+        pub presets_popup: bool,
+        pub presets_position: usize,
+        pub preset_name: Option<String>,
+        pub escape_popup: bool,
+        pub escape_position: usize,
+        pub mouse: bool,
+        pub keymap: Keymap,
+        pub keymap_name: String,
+        /// The `[keys]` overrides the keymap was built with, saved back as they are.
+        pub key_bindings: BTreeMap<String, String>,
+        pub confirm_quit: bool,
+        pub quit_popup: bool,
+        /// The result panel shows bullets instead of the password, which stays copyable.
+        pub pwd_masked: bool,
+        /// Explicitly chosen theme; `None` means dark, or monochrome under `NO_COLOR`.
+        pub theme: Option<String>,
+        pub no_color: bool,
+        pub click_areas: Vec<(String, Rect)>,
+        pub viewer_popup: bool,
+        pub viewer_scroll: u16,
+        pub help_popup: bool,
+        pub help_scroll: u16,
+        pub history: Vec<HistoryEntry>,
+        pub history_panel: bool,
+        pub history_position: usize,
+        pub history_label: Option<String>,
+        pub pwd_accepted: bool,
+        pub idle_timeout_secs: u64,
+        pub last_input: Instant,
+        pub pwd_idle_hidden: bool,
         pub lang: String,
+        pub label: String,
+        pub hook: Option<String>,
+        pub template: Option<String>,
+        pub notice: (String, String),
         pub errors: (String, String),
     }
 
-    impl Generator {
-        pub fn default() -> Generator {
+    impl Default for Generator {
+        fn default() -> Generator {
             Generator {
                 letters: false,
                 u_letters: false,
@@ -40,19 +122,74 @@ pub mod generator {
                 pwd_len: "8".to_string(),
                 min_pwd_len: 4,
                 max_pwd_len: 10000,
+                policy: AdminPolicy::default(),
+                tab: "password".to_string(),
+                words: "5".to_string(),
+                separator: "-".to_string(),
+                capitalize: true,
+                add_number: false,
+                pin_len: "6".to_string(),
+                pin_no_patterns: true,
+                token_bytes: "32".to_string(),
+                token_encoding: "hex".to_string(),
+                id_kind: "uuid-v4".to_string(),
+                analyze_input: "".to_string(),
                 pwd: "".to_string(),
+                pwd_copied: false,
+                clipboard_target: "clipboard".to_string(),
+                auto_copy: true,
+                focus_lost: false,
+                pick_mode: false,
+                presets: Vec::new(),
+                active_preset: "".to_string(),
+                presets_popup: false,
+                presets_position: 0,
+                preset_name: None,
+                escape_popup: false,
+                escape_position: 0,
+                mouse: true,
+                keymap: Keymap::default(),
+                keymap_name: "default".to_string(),
+                key_bindings: BTreeMap::new(),
+                confirm_quit: true,
+                quit_popup: false,
+                pwd_masked: false,
+                theme: None,
+                no_color: false,
+                click_areas: Vec::new(),
+                viewer_popup: false,
+                viewer_scroll: 0,
+                help_popup: false,
+                help_scroll: 0,
+                history: Vec::new(),
+                history_panel: false,
+                history_position: 0,
+                history_label: None,
+                pwd_accepted: false,
+                idle_timeout_secs: 300,
+                last_input: Instant::now(),
+                pwd_idle_hidden: false,
                 lang: "en".to_string(),
+                label: "".to_string(),
+                hook: None,
+                template: None,
+                notice: ("".to_string(), "".to_string()),
                 errors: ("".to_string(), "".to_string()),
             }
         }
+    }
 
+    impl Generator {
         pub fn get_rule_state(&self, rule_name: &str) -> bool {
             match rule_name {
-                "letters" => self.letters.clone(),
-                "u_letters" => self.u_letters.clone(),
-                "numbs" => self.numbs.clone(),
-                "spec_symbs" => self.spec_symbs.clone(),
-                "convenience_criterion" => self.convenience_criterion.clone(),
+                "letters" => self.letters,
+                "u_letters" => self.u_letters,
+                "numbs" => self.numbs,
+                "spec_symbs" => self.spec_symbs,
+                "convenience_criterion" => self.convenience_criterion,
+                "capitalize" => self.capitalize,
+                "add_number" => self.add_number,
+                "pin_no_patterns" => self.pin_no_patterns,
                 _ => true,
             }
         }
@@ -64,6 +201,9 @@ pub mod generator {
                 "numbs" => self.numbs = new_val,
                 "spec_symbs" => self.spec_symbs = new_val,
                 "convenience_criterion" => self.convenience_criterion = new_val,
+                "capitalize" => self.capitalize = new_val,
+                "add_number" => self.add_number = new_val,
+                "pin_no_patterns" => self.pin_no_patterns = new_val,
                 _ => {}
             }
         }
@@ -87,43 +227,89 @@ pub mod generator {
         }
 
         pub fn enter_char(&mut self, new_char: char) {
-            if &new_char == &' ' {
-                if self.rules_position != "pwd_len" {
-                    let cur_status = self.get_rule_state(&self.rules_position).clone();
-                    self.set_rule_state(
-                        &self.rules_position.clone(),
-                        if cur_status { false } else { true },
+            if self.rules_position == "analyze_input" {
+                self.insert_char(new_char);
+            } else if new_char == ' ' {
+                self.toggle_field();
+            } else if new_char.is_ascii_digit() && self.input().is_some() {
+                self.insert_char(new_char);
+            };
+        }
+
+        /// Whether the focused field takes `ch` as text instead of a key binding.
+        pub fn accepts_char(&self, ch: char) -> bool {
+            self.rules_position == "analyze_input"
+                || (ch.is_ascii_digit() && self.input().is_some())
+        }
+
+        /// Toggles the focused rule or switches the focused choice.
+        pub fn toggle_field(&mut self) {
+            if let Some(choices) = self.choices() {
+                self.cycle_choice(choices);
+            } else if self.input().is_none() {
+                let cur_status = self.get_rule_state(&self.rules_position);
+                if cur_status && self.policy.is_required(&self.rules_position) {
+                    self.errors = (
+                        "This rule is required by the administrator policy!".to_string(),
+                        "Это правило обязательно по политике администратора!".to_string(),
                     );
-                };
-            } else {
-                self.pwd_len.insert(self.cursor_position, new_char);
-                self.move_cursor_right();
+                    return;
+                }
+                self.set_rule_state(&self.rules_position.clone(), !cur_status);
+                self.edited();
+            };
+        }
+
+        fn insert_char(&mut self, new_char: char) {
+            let cursor_position = self.cursor_position;
+            let Some(input) = self.input_mut() else {
+                return;
             };
+            let byte_idx = input
+                .char_indices()
+                .nth(cursor_position)
+                .map(|(i, _)| i)
+                .unwrap_or(input.len());
+            input.insert(byte_idx, new_char);
+            self.move_cursor_right();
+            self.edited();
         }
 
         pub fn backspace_char(&mut self) {
             let is_not_cursor_leftmost = self.cursor_position != 0;
-            if is_not_cursor_leftmost {
-                let current_index = self.cursor_position;
-                let before_char_to_delete = self.pwd_len.chars().take(current_index - 1);
-                let after_char_to_delete = self.pwd_len.chars().skip(current_index);
-                self.pwd_len = before_char_to_delete.chain(after_char_to_delete).collect();
+            let current_index = self.cursor_position;
+            if let (true, Some(input)) = (is_not_cursor_leftmost, self.input_mut()) {
+                let before_char_to_delete = input.chars().take(current_index - 1);
+                let after_char_to_delete = input.chars().skip(current_index);
+                *input = before_char_to_delete.chain(after_char_to_delete).collect();
                 self.move_cursor_left();
+                self.edited();
             }
         }
 
         pub fn delete_char(&mut self) {
-            let is_not_cursor_rightmost = self.cursor_position != self.pwd_len.parse().unwrap();
+            let current_index = self.cursor_position;
+            let Some(input) = self.input_mut() else {
+                return;
+            };
+            let is_not_cursor_rightmost = current_index < input.chars().count();
             if is_not_cursor_rightmost {
-                let current_index = self.cursor_position;
-                let before_char_to_delete = self.pwd_len.chars().take(current_index);
-                let after_char_to_delete = self.pwd_len.chars().skip(current_index + 1);
-                self.pwd_len = before_char_to_delete.chain(after_char_to_delete).collect();
+                let before_char_to_delete = input.chars().take(current_index);
+                let after_char_to_delete = input.chars().skip(current_index + 1);
+                *input = before_char_to_delete.chain(after_char_to_delete).collect();
+                self.edited();
+            }
+        }
+
+        /// Marks the password rules as changed by hand, which detaches the active preset.
+        fn edited(&mut self) {
+            if self.tab == "password" {
+                self.active_preset.clear();
             }
         }
 
         pub fn clamp_cursor(&self, new_cursor_pos: usize) -> usize {
-            new_cursor_pos.clamp(0, self.pwd_len.len())
+            new_cursor_pos.clamp(0, self.input_len())
         }
 
         pub fn reset_cursor(&mut self) {
@@ -131,123 +317,603 @@ pub mod generator {
         }
 
         pub fn cursor_to_end(&mut self) {
-            self.cursor_position = self.pwd_len.len();
+            self.cursor_position = self.input_len();
         }
 
-        pub fn circ_cursor(&mut self) {
-            let circ_last_idx = CIRCUITED_FIELDS.len() - 1;
-            let cur_index = CIRCUITED_FIELDS
+        /// The focused text field, if focus is not on a toggle or a choice.
+        pub fn input(&self) -> Option<&String> {
+            match self.rules_position.as_str() {
+                "pwd_len" => Some(&self.pwd_len),
+                "words" => Some(&self.words),
+                "pin_len" => Some(&self.pin_len),
+                "token_bytes" => Some(&self.token_bytes),
+                "analyze_input" => Some(&self.analyze_input),
+                _ => None,
+            }
+        }
+
+        fn input_mut(&mut self) -> Option<&mut String> {
+            match self.rules_position.as_str() {
+                "pwd_len" => Some(&mut self.pwd_len),
+                "words" => Some(&mut self.words),
+                "pin_len" => Some(&mut self.pin_len),
+                "token_bytes" => Some(&mut self.token_bytes),
+                "analyze_input" => Some(&mut self.analyze_input),
+                _ => None,
+            }
+        }
+
+        fn input_len(&self) -> usize {
+            self.input().map(|input| input.chars().count()).unwrap_or(0)
+        }
+
+        /// Allowed values of a number field, `None` for other fields.
+        pub fn field_range(&self, field: &str) -> Option<(u32, u32)> {
+            match field {
+                "pwd_len" => Some((self.min_pwd_len, self.max_pwd_len)),
+                "words" => Some(WORDS_RANGE),
+                "pin_len" => Some(PIN_RANGE),
+                "token_bytes" => Some(TOKEN_RANGE),
+                _ => None,
+            }
+        }
+
+        /// Adds `delta` to the focused number field, keeping it in the allowed range.
+        pub fn step_input(&mut self, delta: i64) {
+            let Some((min, max)) = self.field_range(&self.rules_position) else {
+                return;
+            };
+            let Some(input) = self.input_mut() else {
+                return;
+            };
+            let value = match input.parse::<i64>() {
+                Ok(value) => value.saturating_add(delta).clamp(min as i64, max as i64),
+                Err(_err) => min as i64,
+            };
+            *input = value.to_string();
+            self.cursor_to_end();
+            self.edited();
+        }
+
+        /// Whether the focused field is a number changed by ▲▼, +/- and PgUp/PgDn.
+        pub fn on_number_field(&self) -> bool {
+            self.field_range(&self.rules_position).is_some()
+        }
+
+        /// Sets the password length to one of `COMMON_LENGTHS`, if the policy allows it.
+        pub fn pick_common_length(&mut self, idx: usize) {
+            let Some(length) = COMMON_LENGTHS.get(idx) else {
+                return;
+            };
+            if self.tab != "password" {
+                return;
+            }
+            if *length < self.min_pwd_len || *length > self.max_pwd_len {
+                self.errors = (
+                    format!(
+                        "Length {} is outside the allowed {} - {}!",
+                        length, self.min_pwd_len, self.max_pwd_len
+                    ),
+                    format!(
+                        "Длина {} вне допустимых {} - {}!",
+                        length, self.min_pwd_len, self.max_pwd_len
+                    ),
+                );
+                return;
+            }
+            self.rules_position = "pwd_len".to_string();
+            self.pwd_len = length.to_string();
+            self.cursor_to_end();
+            self.edited();
+        }
+
+        /// Name and position of the clickable area under the mouse, as of the last draw.
+        pub fn area_at(&self, column: u16, row: u16) -> Option<(String, Rect)> {
+            self.click_areas
                 .iter()
-                .position(|&r| &r == &self.rules_position)
-                .unwrap();
+                .rev()
+                .find(|(_, area)| area.contains((column, row).into()))
+                .cloned()
+        }
+
+        /// Moves focus to a field of the current tab, with the cursor at `column` of a text field.
+        pub fn focus_field(&mut self, field: &str, column: usize) {
+            if !self.tab_fields().contains(&field) {
+                return;
+            }
+            self.rules_position = field.to_string();
+            self.cursor_position = self.clamp_cursor(column);
+        }
+
+        /// Values of the focused choice field, switched with Space.
+        pub fn choices(&self) -> Option<&'static [&'static str]> {
+            match self.rules_position.as_str() {
+                "separator" => Some(&SEPARATORS),
+                "token_encoding" => Some(&TOKEN_ENCODINGS),
+                "id_kind" => Some(&ID_KINDS),
+                _ => None,
+            }
+        }
+
+        fn cycle_choice(&mut self, choices: &[&str]) {
+            let value = match self.rules_position.as_str() {
+                "separator" => &mut self.separator,
+                "token_encoding" => &mut self.token_encoding,
+                _ => &mut self.id_kind,
+            };
+            let cur_index = choices.iter().position(|c| c == value).unwrap_or(0);
+            *value = choices[(cur_index + 1) % choices.len()].to_string();
+        }
+
+        /// Fields of the current tab in focus order.
+        pub fn tab_fields(&self) -> &'static [&'static str] {
+            match self.tab.as_str() {
+                "passphrase" => &["words", "separator", "capitalize", "add_number"],
+                "pin" => &["pin_len", "pin_no_patterns"],
+                "token" => &["token_bytes", "token_encoding"],
+                "identifier" => &["id_kind"],
+                "analyze" => &["analyze_input"],
+                _ => &CIRCUITED_FIELDS,
+            }
+        }
+
+        pub fn switch_tab(&mut self, forward: bool) {
+            let cur_index = TABS.iter().position(|&t| t == self.tab).unwrap_or(0);
+            let next_index = if forward {
+                (cur_index + 1) % TABS.len()
+            } else {
+                (cur_index + TABS.len() - 1) % TABS.len()
+            };
+            self.select_tab(TABS[next_index]);
+        }
+
+        pub fn select_tab(&mut self, tab: &str) {
+            if self.tab == tab || !TABS.contains(&tab) {
+                return;
+            }
+            self.tab = tab.to_string();
+            self.rules_position = self.tab_fields()[0].to_string();
+            self.cursor_to_end();
+            wipe_secret(&mut self.pwd);
+            self.pwd_copied = false;
+            self.pwd_idle_hidden = false;
+            self.errors = ("".to_string(), "".to_string());
+            self.notice = ("".to_string(), "".to_string());
+        }
+
+        pub fn circ_cursor(&mut self) {
+            let fields = self.tab_fields();
+            let circ_last_idx = fields.len() - 1;
+            let Some(cur_index) = self.rules_index() else {
+                return;
+            };
             if cur_index < circ_last_idx {
-                self.rules_position = CIRCUITED_FIELDS[cur_index + 1].to_string();
+                self.rules_position = fields[cur_index + 1].to_string();
             } else {
-                self.rules_position = CIRCUITED_FIELDS[0].to_string();
+                self.rules_position = fields[0].to_string();
             }
+            self.cursor_to_end();
         }
 
         pub fn up_cursor(&mut self) {
-            let circ_last_idx = CIRCUITED_FIELDS.len() - 1;
-            let cur_index = CIRCUITED_FIELDS
-                .iter()
-                .position(|&r| &r == &self.rules_position)
-                .unwrap();
+            let fields = self.tab_fields();
+            let circ_last_idx = fields.len() - 1;
+            let Some(cur_index) = self.rules_index() else {
+                return;
+            };
             if cur_index > 0 {
-                self.rules_position = CIRCUITED_FIELDS[cur_index - 1].to_string();
+                self.rules_position = fields[cur_index - 1].to_string();
             } else {
-                self.rules_position = CIRCUITED_FIELDS[circ_last_idx].to_string();
+                self.rules_position = fields[circ_last_idx].to_string();
+            }
+            self.cursor_to_end();
+        }
+
+        fn rules_index(&mut self) -> Option<usize> {
+            let fields = self.tab_fields();
+            let cur_index = fields.iter().position(|&r| r == self.rules_position);
+            if cur_index.is_none() {
+                self.errors = (
+                    format!("Unknown field '{}', focus reset", self.rules_position),
+                    format!("Неизвестное поле '{}', фокус сброшен", self.rules_position),
+                );
+                self.rules_position = fields[0].to_string();
+                self.cursor_to_end();
             }
+            cur_index
         }
 
         pub fn submit_to_pwd(&mut self) {
-            if self.is_valid_user_input() {
-                let mut pwd = self.generate_pass();
-                while !self.is_valid_pwd_by_consist(pwd.clone()) {
-                    pwd = self.generate_pass();
-                }
-                self.pwd = pwd;
-
-                if cfg!(unix) {
-                    let pipe = Command::new("echo")
-                        .arg("-n")
-                        .arg(self.pwd.clone())
-                        .stdout(Stdio::piped())
-                        .spawn();
-                    if let Err(_err) = &pipe {
-                        self.errors = (
-                            "echo error by copy to clipbord!".to_string(),
-                            "При вставке в буфер обмена произошла ошибка echo!".to_string(),
-                        );
-                    } else {
-                        let pipe_out = pipe
-                            .unwrap()
-                            .stdout
-                            .take()
-                            .expect("Failed to take pipe stdout!");
-                        let out = Command::new("xclip")
-                            .arg("-selection")
-                            .arg("clipboard")
-                            .stdin(pipe_out)
-                            .spawn();
-                        if let Err(_err) = &out {
-                            self.errors = (
-                                "\'xclip\' packet needed for copy to clipbord!".to_string(),
-                                "Для вставки в буфер обмена установите пакет \'xclip\'!"
-                                    .to_string(),
-                            );
-                        } else {
-                            let owait = out.unwrap().wait();
-                            if let Err(_err) = &owait {
-                                self.errors = (
-                                    "Failed to run xclip!".to_string(),
-                                    "Failed to run xclip!".to_string(),
-                                );
-                                owait.unwrap();
-                            }
-                        }
+            if self.tab == "analyze" {
+                return;
+            }
+            if self.tab != "password" || self.is_valid_user_input() {
+                self.errors = ("".to_string(), "".to_string());
+                let pwd = match self.generate_current() {
+                    Ok(pwd) => pwd,
+                    Err(err) => {
+                        self.errors = err;
+                        return;
                     }
-                } else {
-                    let clipboard = Clipboard::new();
-                    if let Err(_err) = &clipboard {
-                        self.errors = (
-                            "Copy to clipboard error!".to_string(),
-                            "Ошибка копирования в буфер обмена!".to_string(),
-                        );
-                    } else {
-                        let clip = clipboard.unwrap().set_text(self.pwd.clone());
-                        if let Err(_err) = &clip {
-                            self.errors = (
-                                "Copy to clipboard error!".to_string(),
-                                "Ошибка копирования в буфер обмена!".to_string(),
-                            );
-                        } else {
-                            clip.unwrap();
+                };
+                self.set_pwd(pwd);
+                self.push_history();
+                self.pwd_copied = false;
+                self.pwd_idle_hidden = false;
+                self.notice = ("".to_string(), "".to_string());
+
+                if self.auto_copy {
+                    self.copy_to_clipboard();
+                }
+                if self.hook.is_some() {
+                    match self.run_hook(&self.pwd) {
+                        Ok(()) => {
+                            self.notice = ("hook: ok".to_string(), "хук: успешно".to_string())
                         }
+                        Err(err) => self.errors = err,
                     }
                 }
             } else {
-                self.cursor_position = 1;
-                self.rules_position = "pwd_len".parse().unwrap();
-                self.pwd_len = "8".parse().unwrap();
+                self.rules_position = "pwd_len".to_string();
+                self.pwd_len = self.min_pwd_len.max(8).to_string();
+                self.cursor_to_end();
             }
 
             //self.pwd_len.clear();
             //self.reset_cursor();
         }
 
+        pub fn switch_presets_popup(&mut self) {
+            self.presets_popup = !self.presets_popup && self.tab == "password";
+            self.preset_name = None;
+            self.presets_position = self
+                .presets
+                .iter()
+                .position(|p| p.name == self.active_preset)
+                .unwrap_or(0);
+        }
+
+        /// Moves the popup selection; the row after the presets is "save current rules".
+        pub fn move_presets_position(&mut self, down: bool) {
+            let rows = self.presets.len() + 1;
+            self.presets_position = if down {
+                (self.presets_position + 1) % rows
+            } else {
+                (self.presets_position + rows - 1) % rows
+            };
+        }
+
+        /// Applies the selected preset, or starts naming a new one on the last row.
+        pub fn submit_presets_position(&mut self) {
+            match self.presets.get(self.presets_position) {
+                Some(preset) => {
+                    let preset = preset.clone();
+                    self.pwd_len = preset.length.to_string();
+                    self.letters = preset.lowercase;
+                    self.u_letters = preset.uppercase;
+                    self.numbs = preset.digits;
+                    self.spec_symbs = preset.symbols;
+                    self.convenience_criterion = preset.strong;
+                    self.active_preset = preset.name;
+                    self.cursor_to_end();
+                    self.presets_popup = false;
+                }
+                None => self.preset_name = Some("".to_string()),
+            }
+        }
+
+        /// Saves the current rules under the typed name, replacing a preset with the same name.
+        pub fn save_preset(&mut self) {
+            let Some(name) = self.preset_name.take() else {
+                return;
+            };
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                return;
+            }
+            let Ok(length) = self.pwd_len.parse::<u32>() else {
+                self.errors = (
+                    "Set a valid password length before saving a preset!".to_string(),
+                    "Задайте корректную длину пароля перед сохранением набора!".to_string(),
+                );
+                return;
+            };
+            let preset = Preset {
+                name: name.clone(),
+                length,
+                lowercase: self.letters,
+                uppercase: self.u_letters,
+                digits: self.numbs,
+                symbols: self.spec_symbs,
+                strong: self.convenience_criterion,
+            };
+            match self.presets.iter().position(|p| p.name == name) {
+                Some(idx) => self.presets[idx] = preset,
+                None => self.presets.push(preset),
+            }
+            self.presets_position = self
+                .presets
+                .iter()
+                .position(|p| p.name == name)
+                .unwrap_or(0);
+            self.active_preset = name;
+        }
+
+        pub fn delete_preset(&mut self) {
+            if self.presets_position < self.presets.len() {
+                let removed = self.presets.remove(self.presets_position);
+                if removed.name == self.active_preset {
+                    self.active_preset.clear();
+                }
+            }
+        }
+
+        pub fn switch_help_popup(&mut self) {
+            self.help_popup = !self.help_popup;
+            self.help_scroll = 0;
+        }
+
+        pub fn scroll_help(&mut self, down: bool, lines: u16) {
+            // The bottom depends on the wrapping and is clamped when drawn
+            self.help_scroll = if down {
+                self.help_scroll.saturating_add(lines)
+            } else {
+                self.help_scroll.saturating_sub(lines)
+            };
+        }
+
+        pub fn switch_viewer_popup(&mut self) {
+            self.viewer_popup = !self.viewer_popup && !self.pwd.is_empty();
+            self.viewer_scroll = 0;
+        }
+
+        /// Scrolls the full password view by rows; the bottom is clamped when drawn.
+        pub fn scroll_viewer(&mut self, down: bool, rows: u16) {
+            let max = u16::try_from(self.pwd.chars().count()).unwrap_or(u16::MAX);
+            self.viewer_scroll = if down {
+                self.viewer_scroll.saturating_add(rows).min(max)
+            } else {
+                self.viewer_scroll.saturating_sub(rows)
+            };
+        }
+
+        pub fn switch_escape_popup(&mut self) {
+            self.escape_popup = !self.escape_popup && !self.pwd.is_empty();
+        }
+
+        pub fn move_escape_position(&mut self, down: bool) {
+            let rows = ESCAPE_VIEWS.len();
+            self.escape_position = if down {
+                (self.escape_position + 1) % rows
+            } else {
+                (self.escape_position + rows - 1) % rows
+            };
+        }
+
+        /// Copies the password escaped for the selected destination.
+        pub fn copy_escaped(&mut self) {
+            let view = ESCAPE_VIEWS[self.escape_position];
+            let mut text = escape_for(view, &self.pwd);
+            self.copy_text(&text);
+            wipe_secret(&mut text);
+            if self.errors.0.is_empty() && self.clipboard_target != "none" {
+                self.notice = (format!("{} copy", view), format!("копия {}", view));
+            }
+        }
+
+        /// Generates passwords until one satisfies the selected rules.
+        pub fn generate_valid_pwd(&mut self) -> Result<String, (String, String)> {
+            if !self.is_valid_user_input() {
+                return Err((
+                    format!(
+                        "Password length must be between {} and {}!",
+                        self.min_pwd_len, self.max_pwd_len
+                    ),
+                    format!(
+                        "Длина пароля должна быть от {} до {}!",
+                        self.min_pwd_len, self.max_pwd_len
+                    ),
+                ));
+            }
+            self.check_policy()?;
+            let mut pwd = self.generate_pass()?;
+            while !self.is_valid_pwd_by_consist(&pwd) {
+                wipe_secret(&mut pwd);
+                pwd = self.generate_pass()?;
+            }
+            Ok(pwd)
+        }
+
+        /// Rejects rules weaker than the administrator policy.
+        fn check_policy(&self) -> Result<(), (String, String)> {
+            if !self.is_convenient_mode() {
+                let classes = [
+                    ("letters", "lowercase letters", "маленькие буквы"),
+                    ("u_letters", "capital letters", "большие буквы"),
+                    ("numbs", "numbers", "цифры"),
+                    ("spec_symbs", "special symbols", "спец. символы"),
+                ];
+                for (rule, en, ru) in classes {
+                    if self.policy.is_required(rule) && !self.get_rule_state(rule) {
+                        return Err((
+                            format!("The administrator policy requires {}!", en),
+                            format!("Политика администратора требует {}!", ru),
+                        ));
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// Rejects secrets weaker than the policy `min_entropy`, on every tab.
+        pub fn check_min_entropy(&self, entropy: f64) -> Result<(), (String, String)> {
+            match self.policy.min_entropy {
+                Some(min_entropy) if entropy < min_entropy => Err((
+                    format!(
+                        "Entropy {:.0} bits is below the policy minimum of {:.0}!",
+                        entropy, min_entropy
+                    ),
+                    format!(
+                        "Энтропия {:.0} бит ниже минимума политики {:.0}!",
+                        entropy, min_entropy
+                    ),
+                )),
+                _ => Ok(()),
+            }
+        }
+
+        /// Replaces the current password, wiping the old one and locking the new one in RAM.
+        pub fn set_pwd(&mut self, pwd: String) {
+            wipe_secret(&mut self.pwd);
+            self.pwd = pwd;
+            lock_secret(&self.pwd);
+        }
+
+        /// Registers user activity for the inactivity timer.
+        pub fn touch(&mut self) {
+            self.last_input = Instant::now();
+        }
+
+        /// Wipes the displayed password once no input came for `idle_timeout_secs` (0 disables).
+        pub fn check_idle(&mut self) {
+            if self.idle_timeout_secs == 0
+                || self.last_input.elapsed().as_secs() < self.idle_timeout_secs
+            {
+                return;
+            }
+            self.viewer_popup = false;
+            self.escape_popup = false;
+            if !self.pwd.is_empty() || !self.analyze_input.is_empty() || !self.history.is_empty() {
+                self.wipe_history();
+                wipe_secret(&mut self.pwd);
+                wipe_secret(&mut self.analyze_input);
+                if self.rules_position == "analyze_input" {
+                    self.reset_cursor();
+                }
+                self.pwd_copied = false;
+                self.pwd_idle_hidden = true;
+            }
+        }
+
+        pub fn switch_clipboard_target(&mut self) {
+            let cur_index = CLIPBOARD_TARGETS
+                .iter()
+                .position(|&t| t == self.clipboard_target)
+                .unwrap_or(0);
+            self.clipboard_target =
+                CLIPBOARD_TARGETS[(cur_index + 1) % CLIPBOARD_TARGETS.len()].to_string();
+        }
+
+        pub fn switch_auto_copy(&mut self) {
+            self.auto_copy = !self.auto_copy;
+        }
+
+        pub fn switch_pwd_masked(&mut self) {
+            self.pwd_masked = !self.pwd_masked;
+        }
+
+        /// Copies the password, formatted by the output template, to the chosen selection.
+        pub fn copy_to_clipboard(&mut self) {
+            if self.pwd.is_empty() {
+                return;
+            }
+            let mut text = self.render_template(&self.pwd);
+            self.copy_text(&text);
+            wipe_secret(&mut text);
+        }
+
+        pub fn copy_text(&mut self, text: &str) {
+            if text.is_empty() || self.clipboard_target == "none" {
+                return;
+            }
+            self.errors = ("".to_string(), "".to_string());
+
+            if cfg!(unix) {
+                let selections: &[&str] = match self.clipboard_target.as_str() {
+                    "primary" => &["primary"],
+                    "both" => &["clipboard", "primary"],
+                    _ => &["clipboard"],
+                };
+                for selection in selections {
+                    if let Err(err) = xclip_copy(selection, text) {
+                        self.errors = err;
+                        return;
+                    }
+                }
+            } else {
+                let clipboard = Clipboard::new();
+                if let Err(_err) = &clipboard {
+                    self.errors = (
+                        "Copy to clipboard error!".to_string(),
+                        "Ошибка копирования в буфер обмена!".to_string(),
+                    );
+                    return;
+                }
+                if let Err(_err) = clipboard.unwrap().set_text(text) {
+                    self.errors = (
+                        "Copy to clipboard error!".to_string(),
+                        "Ошибка копирования в буфер обмена!".to_string(),
+                    );
+                    return;
+                }
+            }
+            self.pwd_copied = true;
+        }
+
         fn is_valid_user_input(&self) -> bool {
             let parse_res = self.pwd_len.parse::<u32>();
             match parse_res {
-                Ok(val) => {
-                    if val < self.min_pwd_len || val > self.max_pwd_len {
-                        false
-                    } else {
-                        true
-                    }
-                }
+                Ok(val) => val >= self.min_pwd_len && val <= self.max_pwd_len,
                 Err(_err) => false,
             }
         }
     }
+
+    impl Drop for Generator {
+        fn drop(&mut self) {
+            wipe_secret(&mut self.pwd);
+            wipe_secret(&mut self.analyze_input);
+            self.wipe_history();
+        }
+    }
+
+    fn xclip_copy(selection: &str, text: &str) -> Result<(), (String, String)> {
+        let child = Command::new("xclip")
+            .arg("-selection")
+            .arg(selection)
+            .stdin(Stdio::piped())
+            // xclip forks to keep serving the selection, a piped stream would never close;
+            // its messages would also draw over the interface
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(_err) => {
+                return Err((
+                    "\'xclip\' packet needed for copy to clipbord!".to_string(),
+                    "Для вставки в буфер обмена установите пакет \'xclip\'!".to_string(),
+                ))
+            }
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Err(_err) = stdin.write_all(text.as_bytes()) {
+                return Err((
+                    "Failed to run xclip!".to_string(),
+                    "Не удалось запустить xclip!".to_string(),
+                ));
+            }
+        }
+        match child.wait() {
+            Ok(status) if status.success() => Ok(()),
+            Ok(_status) => Err((
+                "xclip failed to copy, is an X display available?".to_string(),
+                "xclip не смог скопировать, доступен ли X-дисплей?".to_string(),
+            )),
+            Err(_err) => Err((
+                "Failed to run xclip!".to_string(),
+                "Не удалось запустить xclip!".to_string(),
+            )),
+        }
+    }
 }
@@ -0,0 +1,122 @@
+pub mod history {
+    use crate::cli::cli::utc_timestamp;
+    use crate::generator::generator::Generator;
+    use crate::secure_mem::secure_mem::{lock_secret, wipe_secret};
+
+    /// Oldest entries are wiped once the session history grows past this size.
+    const HISTORY_LIMIT: usize = 100;
+
+    /// A secret generated in this session. Lives in RAM only and is wiped on exit.
+    pub struct HistoryEntry {
+        pub secret: String,
+        pub time: String,
+        pub tab: String,
+        pub label: String,
+        /// `{policy}` and `{entropy}` as they were when the secret was made.
+        pub policy: String,
+        pub entropy: f64,
+        pub revealed: bool,
+    }
+
+    impl Generator {
+        /// Remembers the current password at the top of the session history.
+        pub fn push_history(&mut self) {
+            if self.pwd.is_empty() {
+                return;
+            }
+            let secret = self.pwd.clone();
+            lock_secret(&secret);
+            let entropy = self.template_entropy(&secret);
+            self.history.insert(
+                0,
+                HistoryEntry {
+                    secret,
+                    time: utc_timestamp()[11..19].to_string(),
+                    tab: self.tab.clone(),
+                    label: self.label.clone(),
+                    policy: self.template_policy().to_string(),
+                    entropy,
+                    revealed: false,
+                },
+            );
+            while self.history.len() > HISTORY_LIMIT {
+                if let Some(mut entry) = self.history.pop() {
+                    wipe_secret(&mut entry.secret);
+                }
+            }
+            self.history_position = 0;
+        }
+
+        pub fn switch_history_panel(&mut self) {
+            self.history_panel = !self.history_panel;
+            self.history_label = None;
+            self.history_position = 0;
+        }
+
+        pub fn move_history_position(&mut self, down: bool) {
+            let rows = self.history.len();
+            if rows == 0 {
+                return;
+            }
+            self.history_position = if down {
+                (self.history_position + 1) % rows
+            } else {
+                (self.history_position + rows - 1) % rows
+            };
+        }
+
+        pub fn reveal_history_entry(&mut self) {
+            if let Some(entry) = self.history.get_mut(self.history_position) {
+                entry.revealed = !entry.revealed;
+            }
+        }
+
+        /// Copies the selected entry again, formatted by the output template
+        /// with the entry's own label, policy and entropy.
+        pub fn copy_history_entry(&mut self) {
+            let Some(entry) = self.history.get(self.history_position) else {
+                return;
+            };
+            let mut text =
+                self.fill_template(&entry.secret, &entry.label, &entry.policy, entry.entropy);
+            let time = entry.time.clone();
+            self.copy_text(&text);
+            wipe_secret(&mut text);
+            if self.errors.0.is_empty() && self.clipboard_target != "none" {
+                self.notice = (
+                    format!("copied entry {}", time),
+                    format!("скопирована запись {}", time),
+                );
+            }
+        }
+
+        pub fn delete_history_entry(&mut self) {
+            if self.history_position < self.history.len() {
+                let mut entry = self.history.remove(self.history_position);
+                wipe_secret(&mut entry.secret);
+                if self.history_position >= self.history.len() {
+                    self.history_position = self.history.len().saturating_sub(1);
+                }
+            }
+        }
+
+        /// Sets the label typed for the selected entry.
+        pub fn save_history_label(&mut self) {
+            let Some(label) = self.history_label.take() else {
+                return;
+            };
+            if let Some(entry) = self.history.get_mut(self.history_position) {
+                entry.label = label.trim().to_string();
+            }
+        }
+
+        pub fn wipe_history(&mut self) {
+            for entry in self.history.iter_mut() {
+                wipe_secret(&mut entry.secret);
+            }
+            self.history.clear();
+            self.history_position = 0;
+            self.history_label = None;
+        }
+    }
+}
@@ -0,0 +1,65 @@
+pub mod hook {
+    use crate::generator::generator::Generator;
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    impl Generator {
+        /// Runs the configured post-generation command with the password on stdin
+        /// and the non-secret metadata in `PASSGEN_*` environment variables.
+        pub fn run_hook(&self, pwd: &str) -> Result<(), (String, String)> {
+            let Some(command) = self.hook.as_deref().filter(|c| !c.trim().is_empty()) else {
+                return Ok(());
+            };
+            let mut shell = if cfg!(windows) {
+                let mut shell = Command::new("cmd");
+                shell.arg("/C");
+                shell
+            } else {
+                let mut shell = Command::new("sh");
+                shell.arg("-c");
+                shell
+            };
+            let child = shell
+                .arg(command)
+                .env("PASSGEN_LABEL", &self.label)
+                .env("PASSGEN_PWD_LENGTH", pwd.chars().count().to_string())
+                .env("PASSGEN_POLICY", self.template_policy())
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .spawn();
+            let mut child = match child {
+                Ok(child) => child,
+                Err(err) => {
+                    return Err((
+                        format!("Hook failed to start: {}", err),
+                        format!("Не удалось запустить хук: {}", err),
+                    ))
+                }
+            };
+            if let Some(mut stdin) = child.stdin.take() {
+                // A hook that ignores stdin closes the pipe early, that is not an error.
+                let _ = stdin.write_all(pwd.as_bytes());
+            }
+            match child.wait_with_output() {
+                Ok(output) if output.status.success() => Ok(()),
+                Ok(output) => {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    let reason = stderr
+                        .lines()
+                        .next()
+                        .map(|line| line.to_string())
+                        .unwrap_or_else(|| output.status.to_string());
+                    Err((
+                        format!("Hook failed: {}", reason),
+                        format!("Ошибка хука: {}", reason),
+                    ))
+                }
+                Err(err) => Err((
+                    format!("Hook failed: {}", err),
+                    format!("Ошибка хука: {}", err),
+                )),
+            }
+        }
+    }
+}
@@ -0,0 +1,381 @@
+pub mod keymap {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::collections::BTreeMap;
+
+    /// Key profiles, chosen with the `keymap` config key or `PASSGEN_KEYMAP`.
+    pub const KEYMAPS: [&str; 3] = ["default", "vim", "emacs"];
+
+    /// What a key does on the main screen.
+    #[derive(Clone, Copy, PartialEq)]
+    pub enum Action {
+        Quit,
+        Help,
+        Lang,
+        Copy,
+        ClipboardTarget,
+        AutoCopy,
+        Generate,
+        Regenerate,
+        Presets,
+        Escape,
+        Mask,
+        History,
+        Viewer,
+        PrevTab,
+        NextTab,
+        PrevField,
+        NextField,
+        /// Previous field, or +1 on a number field.
+        Up,
+        /// Next field, or -1 on a number field.
+        Down,
+        Increase,
+        Decrease,
+        StepUp,
+        StepDown,
+        CommonLength(usize),
+        Toggle,
+        CursorLeft,
+        CursorRight,
+        CursorHome,
+        CursorEnd,
+        Backspace,
+        Delete,
+    }
+
+    /// Action names used in the `[keys]` config table.
+    pub const ACTIONS: [(&str, Action); 35] = [
+        ("quit", Action::Quit),
+        ("help", Action::Help),
+        ("lang", Action::Lang),
+        ("copy", Action::Copy),
+        ("clipboard", Action::ClipboardTarget),
+        ("auto-copy", Action::AutoCopy),
+        ("generate", Action::Generate),
+        ("regenerate", Action::Regenerate),
+        ("presets", Action::Presets),
+        ("escape", Action::Escape),
+        ("mask", Action::Mask),
+        ("history", Action::History),
+        ("viewer", Action::Viewer),
+        ("prev-tab", Action::PrevTab),
+        ("next-tab", Action::NextTab),
+        ("prev-field", Action::PrevField),
+        ("next-field", Action::NextField),
+        ("up", Action::Up),
+        ("down", Action::Down),
+        ("increase", Action::Increase),
+        ("decrease", Action::Decrease),
+        ("step-up", Action::StepUp),
+        ("step-down", Action::StepDown),
+        ("length-12", Action::CommonLength(0)),
+        ("length-16", Action::CommonLength(1)),
+        ("length-20", Action::CommonLength(2)),
+        ("length-32", Action::CommonLength(3)),
+        ("length-64", Action::CommonLength(4)),
+        ("toggle", Action::Toggle),
+        ("cursor-left", Action::CursorLeft),
+        ("cursor-right", Action::CursorRight),
+        ("cursor-home", Action::CursorHome),
+        ("cursor-end", Action::CursorEnd),
+        ("backspace", Action::Backspace),
+        ("delete", Action::Delete),
+    ];
+
+    pub struct Keymap {
+        bindings: Vec<(KeyCode, KeyModifiers, Action)>,
+    }
+
+    impl Default for Keymap {
+        fn default() -> Keymap {
+            Keymap::profile("default")
+        }
+    }
+
+    impl Keymap {
+        /// Bindings of a profile; vim and emacs add their keys to the default ones.
+        pub fn profile(name: &str) -> Keymap {
+            let none = KeyModifiers::NONE;
+            let ctrl = KeyModifiers::CONTROL;
+            let alt = KeyModifiers::ALT;
+            let mut bindings = vec![
+                (KeyCode::Esc, none, Action::Quit),
+                (KeyCode::F(1), none, Action::Help),
+                (KeyCode::Char('?'), none, Action::Help),
+                (KeyCode::F(9), none, Action::Lang),
+                (KeyCode::F(2), none, Action::Copy),
+                (KeyCode::F(3), none, Action::ClipboardTarget),
+                (KeyCode::F(4), none, Action::AutoCopy),
+                (KeyCode::Enter, none, Action::Generate),
+                (KeyCode::F(5), none, Action::Regenerate),
+                (KeyCode::F(6), none, Action::Presets),
+                (KeyCode::F(7), none, Action::Escape),
+                (KeyCode::F(8), none, Action::Mask),
+                (KeyCode::F(10), none, Action::History),
+                (KeyCode::F(12), none, Action::Viewer),
+                (KeyCode::Left, ctrl, Action::PrevTab),
+                (KeyCode::Right, ctrl, Action::NextTab),
+                (KeyCode::BackTab, none, Action::PrevField),
+                (KeyCode::Tab, none, Action::NextField),
+                (KeyCode::Up, none, Action::Up),
+                (KeyCode::Down, none, Action::Down),
+                (KeyCode::Char('+'), none, Action::Increase),
+                (KeyCode::Char('-'), none, Action::Decrease),
+                (KeyCode::PageUp, none, Action::StepUp),
+                (KeyCode::PageDown, none, Action::StepDown),
+                (KeyCode::Char(' '), none, Action::Toggle),
+                (KeyCode::Left, none, Action::CursorLeft),
+                (KeyCode::Right, none, Action::CursorRight),
+                (KeyCode::Home, none, Action::CursorHome),
+                (KeyCode::End, none, Action::CursorEnd),
+                (KeyCode::Backspace, none, Action::Backspace),
+                (KeyCode::Delete, none, Action::Delete),
+            ];
+            for (idx, digit) in ['1', '2', '3', '4', '5'].into_iter().enumerate() {
+                bindings.push((KeyCode::Char(digit), alt, Action::CommonLength(idx)));
+            }
+            match name {
+                "vim" => {
+                    // Esc only leaves popups here, it is pressed out of habit too often
+                    bindings.retain(|(code, _, _)| *code != KeyCode::Esc);
+                    bindings.extend([
+                        (KeyCode::Char('q'), none, Action::Quit),
+                        (KeyCode::Char('j'), none, Action::NextField),
+                        (KeyCode::Char('k'), none, Action::PrevField),
+                        (KeyCode::Char('h'), none, Action::PrevTab),
+                        (KeyCode::Char('l'), none, Action::NextTab),
+                        (KeyCode::Char('y'), none, Action::Copy),
+                        (KeyCode::Char('r'), none, Action::Regenerate),
+                    ]);
+                }
+                "emacs" => bindings.extend([
+                    (KeyCode::Char('g'), ctrl, Action::Quit),
+                    (KeyCode::Char('n'), ctrl, Action::NextField),
+                    (KeyCode::Char('p'), ctrl, Action::PrevField),
+                    (KeyCode::Char('b'), ctrl, Action::CursorLeft),
+                    (KeyCode::Char('f'), ctrl, Action::CursorRight),
+                    (KeyCode::Char('a'), ctrl, Action::CursorHome),
+                    (KeyCode::Char('e'), ctrl, Action::CursorEnd),
+                    (KeyCode::Char('d'), ctrl, Action::Delete),
+                    (KeyCode::Char('w'), alt, Action::Copy),
+                ]),
+                _ => {}
+            }
+            Keymap { bindings }
+        }
+
+        /// A profile with the `[keys]` overrides, e.g. `copy = "y"` or `quit = "ctrl+q, q"`.
+        /// An overridden action loses its profile keys, a reused key leaves its old action.
+        pub fn build(name: &str, keys: &BTreeMap<String, String>) -> Result<Keymap, String> {
+            let mut keymap = Keymap::profile(name);
+            for (action_name, keys) in keys {
+                let Some((_, action)) = ACTIONS.iter().find(|(name, _)| name == action_name) else {
+                    return Err(format!("unknown action '{}' in [keys]", action_name));
+                };
+                keymap.bindings.retain(|(_, _, bound)| bound != action);
+                for key in keys.split(',').map(str::trim).filter(|k| !k.is_empty()) {
+                    let Some((code, modifiers)) = parse_key(key) else {
+                        return Err(format!("unknown key '{}' for {}", key, action_name));
+                    };
+                    keymap
+                        .bindings
+                        .retain(|(c, m, _)| (*c, *m) != (code, modifiers));
+                    keymap.bindings.push((code, modifiers, *action));
+                }
+            }
+            Ok(keymap)
+        }
+
+        pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+            let modifiers = normalize(key.code, key.modifiers);
+            self.bindings
+                .iter()
+                .find(|(code, mods, _)| *code == key.code && *mods == modifiers)
+                .map(|(_, _, action)| *action)
+        }
+
+        /// Keys bound to the actions, as shown in the legend and the help.
+        pub fn key_names(&self, actions: &[Action]) -> Vec<String> {
+            actions
+                .iter()
+                .flat_map(|action| {
+                    self.bindings
+                        .iter()
+                        .filter(move |(_, _, bound)| bound == action)
+                })
+                .map(|(code, modifiers, _)| key_name(*code, *modifiers))
+                .collect()
+        }
+    }
+
+    /// Shift is part of the character itself: `?` and `Y` come with or without it.
+    fn normalize(code: KeyCode, modifiers: KeyModifiers) -> KeyModifiers {
+        match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        }
+    }
+
+    /// Parses keys like `f2`, `y`, `ctrl+g`, `alt+1`, `shift+tab`, `pagedown`.
+    pub fn parse_key(text: &str) -> Option<(KeyCode, KeyModifiers)> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        loop {
+            let lower = rest.to_lowercase();
+            if let Some(key) = lower.strip_prefix("ctrl+") {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = &rest[rest.len() - key.len()..];
+            } else if let Some(key) = lower.strip_prefix("alt+") {
+                modifiers |= KeyModifiers::ALT;
+                rest = &rest[rest.len() - key.len()..];
+            } else if let Some(key) = lower.strip_prefix("shift+") {
+                modifiers |= KeyModifiers::SHIFT;
+                rest = &rest[rest.len() - key.len()..];
+            } else {
+                break;
+            }
+        }
+        let code = match rest.to_lowercase().as_str() {
+            "esc" | "escape" => KeyCode::Esc,
+            "enter" | "return" => KeyCode::Enter,
+            "space" => KeyCode::Char(' '),
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdn" => KeyCode::PageDown,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "plus" => KeyCode::Char('+'),
+            "minus" => KeyCode::Char('-'),
+            key => match key.strip_prefix('f').map(str::parse::<u8>) {
+                Some(Ok(num)) if (1..=12).contains(&num) => KeyCode::F(num),
+                _ => {
+                    let mut chars = rest.chars();
+                    match (chars.next(), chars.next()) {
+                        // `shift+y` is the `Y` the terminal sends, shift itself is dropped below
+                        (Some(ch), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                            KeyCode::Char(ch.to_uppercase().next().unwrap_or(ch))
+                        }
+                        (Some(ch), None) => KeyCode::Char(ch),
+                        _ => return None,
+                    }
+                }
+            },
+        };
+        Some((code, normalize(code, modifiers)))
+    }
+
+    fn key_name(code: KeyCode, modifiers: KeyModifiers) -> String {
+        let key = match code {
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(ch) => ch.to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::BackTab => "Shift+Tab".to_string(),
+            KeyCode::Backspace => "Backspace".to_string(),
+            KeyCode::Delete => "Delete".to_string(),
+            KeyCode::Insert => "Insert".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::Up => "▲".to_string(),
+            KeyCode::Down => "▼".to_string(),
+            KeyCode::Left => "◀".to_string(),
+            KeyCode::Right => "▶".to_string(),
+            KeyCode::F(num) => format!("F{}", num),
+            _ => "?".to_string(),
+        };
+        let mut name = String::new();
+        if modifiers.contains(KeyModifiers::CONTROL) {
+            name.push_str("Ctrl+");
+        }
+        if modifiers.contains(KeyModifiers::ALT) {
+            name.push_str("Alt+");
+        }
+        if modifiers.contains(KeyModifiers::SHIFT) {
+            name.push_str("Shift+");
+        }
+        name + &key
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn parse_key_reads_plain_and_named_keys() {
+            assert_eq!(
+                parse_key("y"),
+                Some((KeyCode::Char('y'), KeyModifiers::NONE))
+            );
+            assert_eq!(parse_key("F2"), Some((KeyCode::F(2), KeyModifiers::NONE)));
+            assert_eq!(
+                parse_key("pagedown"),
+                Some((KeyCode::PageDown, KeyModifiers::NONE))
+            );
+            assert_eq!(
+                parse_key("space"),
+                Some((KeyCode::Char(' '), KeyModifiers::NONE))
+            );
+            assert_eq!(
+                parse_key("plus"),
+                Some((KeyCode::Char('+'), KeyModifiers::NONE))
+            );
+        }
+
+        #[test]
+        fn parse_key_reads_modifiers() {
+            assert_eq!(
+                parse_key("ctrl+g"),
+                Some((KeyCode::Char('g'), KeyModifiers::CONTROL))
+            );
+            assert_eq!(
+                parse_key("Alt+1"),
+                Some((KeyCode::Char('1'), KeyModifiers::ALT))
+            );
+            assert_eq!(
+                parse_key("ctrl+alt+delete"),
+                Some((KeyCode::Delete, KeyModifiers::CONTROL | KeyModifiers::ALT))
+            );
+            assert_eq!(
+                parse_key("shift+up"),
+                Some((KeyCode::Up, KeyModifiers::SHIFT))
+            );
+        }
+
+        #[test]
+        fn parse_key_folds_shift_into_the_character() {
+            assert_eq!(
+                parse_key("shift+y"),
+                Some((KeyCode::Char('Y'), KeyModifiers::NONE))
+            );
+            assert_eq!(parse_key("shift+y"), parse_key("Y"));
+            assert_eq!(
+                parse_key("ctrl+shift+y"),
+                Some((KeyCode::Char('Y'), KeyModifiers::CONTROL))
+            );
+            assert_eq!(
+                parse_key("shift+tab"),
+                Some((KeyCode::BackTab, KeyModifiers::NONE))
+            );
+        }
+
+        #[test]
+        fn parse_key_rejects_unknown_keys() {
+            assert_eq!(parse_key(""), None);
+            assert_eq!(parse_key("ctrl+"), None);
+            assert_eq!(parse_key("f13"), None);
+            assert_eq!(parse_key("hyper+x"), None);
+        }
+    }
+}
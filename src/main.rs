@@ -1,87 +1,518 @@
+#![allow(clippy::module_inception)]
+
+pub mod cli;
+pub mod config;
+pub mod escape;
 mod gen_engine;
 pub mod generator;
+mod history;
+mod hook;
+pub mod keymap;
+mod modes;
+pub mod policy;
+pub mod secure_mem;
+mod template;
+pub mod theme;
 pub mod ui;
 
+use crate::cli::cli::{parse_args, run_cli, CliCommand, USAGE};
+use crate::config::config::Config;
 use crate::generator::generator::Generator;
+use crate::keymap::keymap::Action;
+use crate::policy::policy::AdminPolicy;
+use crate::secure_mem::secure_mem::{disable_core_dumps, wipe_secret};
 use crate::ui::ui::draw_ui;
 use crossterm::{
+    cursor::Show,
     event::*,
+    style::force_color_output,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
 use ratatui::prelude::{Backend, CrosstermBackend};
 use ratatui::Terminal;
-use std::{error::Error, io};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{env, error::Error, io, process, time::Duration};
+
+/// Set in `--pick` mode, where the interface is drawn on the terminal device
+/// and stdout is reserved for the chosen password.
+static PICK_MODE: AtomicBool = AtomicBool::new(false);
 
 fn main() -> Result<(), Box<dyn Error>> {
+    disable_core_dumps();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut generator = Generator::default();
+    // A broken config file is reported and left untouched on exit.
+    let mut save_config = true;
+    // The administrator policy fails closed: a broken or unreadable file stops the app.
+    let policy = match AdminPolicy::load() {
+        Ok(policy) => policy,
+        Err(err) => {
+            eprintln!("error: invalid administrator policy {}", err);
+            process::exit(1);
+        }
+    };
+
+    let command = parse_args(&args);
+    // Scripts get the documented defaults, not the rules saved by the last interface session
+    let scripted = matches!(command, Ok(CliCommand::Generate(_)));
+    let file_config = match Config::load() {
+        Ok(config) if scripted => config.without_rules(),
+        Ok(config) => config,
+        Err(err) => {
+            save_config = false;
+            generator.errors = (
+                format!("Config error: {}", err),
+                format!("Ошибка конфигурации: {}", err),
+            );
+            Config::default()
+        }
+    };
+    file_config.apply_to(&mut generator);
+    let (env_config, env_errors) = Config::from_env();
+    let env_error = (!env_errors.is_empty()).then(|| env_errors.join(", "));
+    if let Some(err) = &env_error {
+        generator.errors = (
+            format!("Environment error: {}", err),
+            format!("Ошибка переменных окружения: {}", err),
+        );
+    }
+    env_config.apply_to(&mut generator);
+    // Applied after the config and environment so they can not start below its floors
+    policy.apply_to(&mut generator);
+    generator.no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    if generator.theme.is_some() {
+        // crossterm drops all colors under NO_COLOR, an explicit theme wins over it
+        force_color_output(true);
+    }
+    match command {
+        Ok(CliCommand::Tui) => {}
+        Ok(CliCommand::Pick(options)) => {
+            PICK_MODE.store(true, Ordering::SeqCst);
+            options.apply_to(&mut generator);
+            generator.pick_mode = true;
+            generator.auto_copy = false;
+        }
+        Ok(CliCommand::Generate(options)) => {
+            // A script must not get a password made without the settings it asked for
+            if let Some(err) = env_error {
+                eprintln!("error: {}", err);
+                process::exit(1);
+            }
+            if !generator.errors.0.is_empty() {
+                eprintln!("warning: {}", generator.errors.0);
+            }
+            if let Err(err) = run_cli(options, generator) {
+                eprintln!("error: {}", err);
+                process::exit(1);
+            }
+            return Ok(());
+        }
+        Ok(CliCommand::Help) => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Ok(CliCommand::Version) => {
+            println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+            return Ok(());
+        }
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    }
+
+    // Settings as they start, the file is only rewritten when the session changed them
+    let initial_config =
+        Config::from_generator(&generator).without_overrides(&file_config, &env_config);
+
+    install_terminal_guards();
     enable_raw_mode()?;
-    let stdout = io::stdout();
-    io::stdout().execute(EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
+    let mut output = tui_output()?;
+    output.execute(EnterAlternateScreen)?;
+    output.execute(EnableFocusChange)?;
+    if generator.mouse {
+        output.execute(EnableMouseCapture)?;
+    }
+    let backend = CrosstermBackend::new(output);
     let mut terminal = Terminal::new(backend)?;
-    let generator = Generator::default();
-    let res = run_app(&mut terminal, generator);
+    let res = run_app(&mut terminal, &mut generator);
 
-    disable_raw_mode()?;
-    io::stdout().execute(LeaveAlternateScreen)?;
-    //terminal.show_cursor()?;
+    restore_terminal()?;
+
+    if let Err(err) = &res {
+        eprintln!("{:?}", err)
+    } else if generator.pwd_accepted {
+        let mut text = generator.render_template(&generator.pwd);
+        println!("{}", text);
+        wipe_secret(&mut text);
+    }
+
+    if res.is_ok() && generator.pick_mode && !generator.pwd_accepted {
+        // Like fzf, a cancelled pick exits with 130 so scripts can tell it from an accepted one
+        drop(generator);
+        process::exit(130);
+    }
+
+    if save_config && !generator.pick_mode {
+        let config =
+            Config::from_generator(&generator).without_overrides(&file_config, &env_config);
+        if config != initial_config {
+            if let Err(err) = config.save() {
+                eprintln!("Failed to save config: {}", err);
+            }
+        }
+    }
 
-    if let Err(err) = res {
-        println!("{:?}", err)
+    Ok(())
+}
+
+/// Where the interface is drawn: stdout, or the terminal device itself in `--pick` mode.
+fn tui_output() -> io::Result<Box<dyn Write + Send>> {
+    if PICK_MODE.load(Ordering::SeqCst) {
+        let tty_path = if cfg!(windows) { "CONOUT$" } else { "/dev/tty" };
+        let tty = OpenOptions::new().read(true).write(true).open(tty_path)?;
+        Ok(Box::new(tty))
+    } else {
+        Ok(Box::new(io::stdout()))
     }
+}
 
+fn restore_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    let mut output = tui_output()?;
+    output.execute(DisableMouseCapture)?;
+    output.execute(DisableFocusChange)?;
+    output.execute(LeaveAlternateScreen)?;
+    output.execute(Show)?;
     Ok(())
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut generator: Generator) -> io::Result<()> {
+/// Leaves raw mode and the alternate screen when the app panics or gets killed.
+fn install_terminal_guards() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal();
+        default_hook(info);
+    }));
+
+    #[cfg(unix)]
+    {
+        use signal_hook::consts::{SIGHUP, SIGQUIT, SIGTERM};
+        use signal_hook::iterator::Signals;
+
+        if let Ok(mut signals) = Signals::new([SIGTERM, SIGHUP, SIGQUIT]) {
+            std::thread::spawn(move || {
+                if let Some(signal) = signals.forever().next() {
+                    let _ = restore_terminal();
+                    std::process::exit(128 + signal);
+                }
+            });
+        }
+    }
+}
+
+fn run_app<B: Backend>(terminal: &mut Terminal<B>, generator: &mut Generator) -> io::Result<()> {
     loop {
-        terminal.draw(|f| draw_ui(f, &generator))?;
+        // Checked on every pass: mouse motion and focus events keep poll from timing out
+        generator.check_idle();
+        terminal.draw(|f| draw_ui(f, generator))?;
 
-        if let Event::Key(key) = read()? {
-            if key.kind == KeyEventKind::Press {
-                match key.code {
-                    KeyCode::Esc => {
-                        return Ok(());
-                    }
-                    KeyCode::F(9) => {
-                        generator.switch_lang();
-                    }
-                    KeyCode::Enter => {
-                        generator.submit_to_pwd();
-                    }
-                    KeyCode::Backspace => {
-                        generator.backspace_char();
-                    }
-                    KeyCode::Delete => {
-                        generator.delete_char();
-                    }
-                    KeyCode::Left => {
-                        generator.move_cursor_left();
-                    }
-                    KeyCode::Right => {
-                        generator.move_cursor_right();
-                    }
-                    KeyCode::Home => {
-                        generator.reset_cursor();
-                    }
-                    KeyCode::End => {
-                        generator.cursor_to_end();
-                    }
-                    KeyCode::Tab => {
-                        generator.circ_cursor();
-                    }
-                    KeyCode::Up => {
-                        generator.up_cursor();
+        if !poll(Duration::from_secs(1))? {
+            continue;
+        }
+
+        match read()? {
+            Event::FocusLost => {
+                generator.focus_lost = true;
+            }
+            Event::FocusGained => {
+                generator.focus_lost = false;
+            }
+            Event::Mouse(mouse) => {
+                // Passing motion is not activity, only clicks and scrolls are
+                if matches!(
+                    mouse.kind,
+                    MouseEventKind::Down(_) | MouseEventKind::ScrollUp | MouseEventKind::ScrollDown
+                ) {
+                    generator.focus_lost = false;
+                    generator.touch();
+                }
+                handle_mouse(generator, mouse);
+            }
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                generator.focus_lost = false;
+                generator.touch();
+                let action = generator.keymap.action(&key);
+                if generator.quit_popup {
+                    match (key.code, action) {
+                        (KeyCode::Esc, _) => generator.quit_popup = false,
+                        (KeyCode::Enter | KeyCode::Char('y'), _) | (_, Some(Action::Quit)) => {
+                            return Ok(());
+                        }
+                        _ => generator.quit_popup = false,
                     }
-                    KeyCode::Down => {
-                        generator.circ_cursor();
+                    continue;
+                }
+                let typing = generator.preset_name.is_some() || generator.history_label.is_some();
+                let code = if typing {
+                    key.code
+                } else {
+                    popup_key(key.code, action)
+                };
+                if generator.help_popup {
+                    handle_help_popup(generator, code);
+                    continue;
+                }
+                if generator.presets_popup {
+                    handle_presets_popup(generator, code);
+                    continue;
+                }
+                if generator.escape_popup {
+                    handle_escape_popup(generator, code);
+                    continue;
+                }
+                if generator.viewer_popup {
+                    handle_viewer_popup(generator, code);
+                    continue;
+                }
+                if generator.history_panel {
+                    handle_history_panel(generator, code);
+                    continue;
+                }
+                let plain = !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+                let quit = match (key.code, action) {
+                    // Text fields take their characters before any letter binding
+                    (KeyCode::Char(ch), _) if plain && generator.accepts_char(ch) => {
+                        generator.enter_char(ch);
+                        false
                     }
-                    KeyCode::Char(to_insert) => {
-                        generator.enter_char(to_insert);
+                    (_, Some(action)) => run_action(generator, action),
+                    (KeyCode::Char(ch), None) if plain => {
+                        generator.enter_char(ch);
+                        false
                     }
-                    _ => {}
+                    _ => false,
+                };
+                if quit {
+                    return Ok(());
                 }
             }
+            _ => {}
+        }
+    }
+}
+
+/// Runs a key binding of the main screen, returns `true` to leave the app.
+fn run_action(generator: &mut Generator, action: Action) -> bool {
+    match action {
+        Action::Quit => {
+            if !generator.confirm_quit {
+                return true;
+            }
+            generator.quit_popup = true;
+        }
+        Action::Help => generator.switch_help_popup(),
+        Action::Lang => generator.switch_lang(),
+        Action::Copy => generator.copy_to_clipboard(),
+        Action::ClipboardTarget => generator.switch_clipboard_target(),
+        Action::AutoCopy => generator.switch_auto_copy(),
+        Action::Generate => {
+            if generator.pick_mode && !generator.pwd.is_empty() {
+                generator.pwd_accepted = true;
+                return true;
+            }
+            generator.submit_to_pwd();
+        }
+        Action::Regenerate => generator.submit_to_pwd(),
+        Action::Presets => generator.switch_presets_popup(),
+        Action::Escape => generator.switch_escape_popup(),
+        Action::Mask => generator.switch_pwd_masked(),
+        Action::History => generator.switch_history_panel(),
+        Action::Viewer => generator.switch_viewer_popup(),
+        Action::PrevTab => generator.switch_tab(false),
+        Action::NextTab => generator.switch_tab(true),
+        Action::PrevField => generator.up_cursor(),
+        Action::NextField => generator.circ_cursor(),
+        Action::Up if generator.on_number_field() => generator.step_input(1),
+        Action::Up => generator.up_cursor(),
+        Action::Down if generator.on_number_field() => generator.step_input(-1),
+        Action::Down => generator.circ_cursor(),
+        Action::Increase => generator.step_input(1),
+        Action::Decrease => generator.step_input(-1),
+        Action::StepUp => generator.step_input(8),
+        Action::StepDown => generator.step_input(-8),
+        Action::CommonLength(idx) => generator.pick_common_length(idx),
+        Action::Toggle => generator.toggle_field(),
+        Action::CursorLeft => generator.move_cursor_left(),
+        Action::CursorRight => generator.move_cursor_right(),
+        Action::CursorHome => generator.reset_cursor(),
+        Action::CursorEnd => generator.cursor_to_end(),
+        Action::Backspace => generator.backspace_char(),
+        Action::Delete => generator.delete_char(),
+    }
+    false
+}
+
+/// Popups keep their own keys; rebound commands are translated to them,
+/// so that e.g. `j`/`k` of the vim keymap move through the lists too.
+fn popup_key(code: KeyCode, action: Option<Action>) -> KeyCode {
+    match action {
+        Some(Action::Quit) => KeyCode::Esc,
+        Some(Action::Help) => KeyCode::F(1),
+        Some(Action::Lang) => KeyCode::F(9),
+        Some(Action::Copy) => KeyCode::F(2),
+        Some(Action::Presets) => KeyCode::F(6),
+        Some(Action::Escape) => KeyCode::F(7),
+        Some(Action::Mask) => KeyCode::F(8),
+        Some(Action::History) => KeyCode::F(10),
+        Some(Action::Viewer) => KeyCode::F(12),
+        Some(Action::Up | Action::PrevField) => KeyCode::Up,
+        Some(Action::Down | Action::NextField) => KeyCode::Down,
+        Some(Action::StepUp) => KeyCode::PageUp,
+        Some(Action::StepDown) => KeyCode::PageDown,
+        _ => code,
+    }
+}
+
+fn handle_mouse(generator: &mut Generator, mouse: MouseEvent) {
+    if generator.viewer_popup {
+        match mouse.kind {
+            MouseEventKind::ScrollUp => generator.scroll_viewer(false, 1),
+            MouseEventKind::ScrollDown => generator.scroll_viewer(true, 1),
+            _ => {}
+        }
+        return;
+    }
+    if generator.quit_popup
+        || generator.help_popup
+        || generator.presets_popup
+        || generator.escape_popup
+        || generator.history_panel
+    {
+        return;
+    }
+    let hovered = generator.area_at(mouse.column, mouse.row);
+    match (mouse.kind, hovered) {
+        (MouseEventKind::Down(MouseButton::Left), Some((name, area))) => {
+            if name == "result" {
+                generator.copy_to_clipboard();
+            } else if let Some(tab) = name.strip_prefix("tab:") {
+                generator.select_tab(tab);
+            } else {
+                let column = mouse.column.saturating_sub(area.x + 1) as usize;
+                generator.focus_field(&name, column);
+                if generator.input().is_none() {
+                    generator.toggle_field();
+                }
+            }
+        }
+        (MouseEventKind::ScrollUp | MouseEventKind::ScrollDown, hovered) => {
+            // Scrolling over a number field focuses it, elsewhere the focused one changes
+            if let Some((name, area)) = hovered {
+                if generator.field_range(&name).is_some() {
+                    generator.focus_field(&name, area.width as usize);
+                }
+            }
+            let delta = if mouse.kind == MouseEventKind::ScrollUp {
+                1
+            } else {
+                -1
+            };
+            generator.step_input(delta);
+        }
+        _ => {}
+    }
+}
+
+fn handle_help_popup(generator: &mut Generator, code: KeyCode) {
+    match code {
+        KeyCode::Esc | KeyCode::F(1) | KeyCode::Char('?') => generator.switch_help_popup(),
+        KeyCode::Up => generator.scroll_help(false, 1),
+        KeyCode::Down => generator.scroll_help(true, 1),
+        KeyCode::PageUp => generator.scroll_help(false, 10),
+        KeyCode::PageDown => generator.scroll_help(true, 10),
+        KeyCode::F(9) => generator.switch_lang(),
+        _ => {}
+    }
+}
+
+fn handle_presets_popup(generator: &mut Generator, code: KeyCode) {
+    if let Some(name) = generator.preset_name.as_mut() {
+        match code {
+            KeyCode::Esc => generator.preset_name = None,
+            KeyCode::Enter => generator.save_preset(),
+            KeyCode::Backspace => {
+                name.pop();
+            }
+            KeyCode::Char(ch) if name.chars().count() < 32 => name.push(ch),
+            _ => {}
+        }
+        return;
+    }
+    match code {
+        KeyCode::Esc | KeyCode::F(6) => generator.switch_presets_popup(),
+        KeyCode::Up => generator.move_presets_position(false),
+        KeyCode::Down | KeyCode::Tab => generator.move_presets_position(true),
+        KeyCode::Enter => generator.submit_presets_position(),
+        KeyCode::Delete => generator.delete_preset(),
+        _ => {}
+    }
+}
+
+fn handle_escape_popup(generator: &mut Generator, code: KeyCode) {
+    match code {
+        KeyCode::Esc | KeyCode::F(7) => generator.switch_escape_popup(),
+        KeyCode::Up => generator.move_escape_position(false),
+        KeyCode::Down | KeyCode::Tab => generator.move_escape_position(true),
+        KeyCode::Enter | KeyCode::F(2) => generator.copy_escaped(),
+        _ => {}
+    }
+}
+
+fn handle_viewer_popup(generator: &mut Generator, code: KeyCode) {
+    match code {
+        KeyCode::Esc | KeyCode::F(12) => generator.switch_viewer_popup(),
+        KeyCode::Up => generator.scroll_viewer(false, 1),
+        KeyCode::Down => generator.scroll_viewer(true, 1),
+        KeyCode::PageUp => generator.scroll_viewer(false, 10),
+        KeyCode::PageDown => generator.scroll_viewer(true, 10),
+        KeyCode::Home => generator.viewer_scroll = 0,
+        KeyCode::End => generator.scroll_viewer(true, u16::MAX),
+        KeyCode::F(2) => generator.copy_to_clipboard(),
+        KeyCode::F(8) => generator.switch_pwd_masked(),
+        _ => {}
+    }
+}
+
+fn handle_history_panel(generator: &mut Generator, code: KeyCode) {
+    if let Some(label) = generator.history_label.as_mut() {
+        match code {
+            KeyCode::Esc => generator.history_label = None,
+            KeyCode::Enter => generator.save_history_label(),
+            KeyCode::Backspace => {
+                label.pop();
+            }
+            KeyCode::Char(ch) if label.chars().count() < 24 => label.push(ch),
+            _ => {}
+        }
+        return;
+    }
+    match code {
+        KeyCode::Esc | KeyCode::F(10) => generator.switch_history_panel(),
+        KeyCode::Up => generator.move_history_position(false),
+        KeyCode::Down | KeyCode::Tab => generator.move_history_position(true),
+        KeyCode::Char(' ') => generator.reveal_history_entry(),
+        KeyCode::Enter | KeyCode::F(2) => generator.copy_history_entry(),
+        KeyCode::Delete => generator.delete_history_entry(),
+        KeyCode::Char('l') if !generator.history.is_empty() => {
+            generator.history_label = Some("".to_string())
         }
+        _ => {}
     }
 }
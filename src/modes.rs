@@ -0,0 +1,347 @@
+pub mod modes {
+    use crate::gen_engine::gen_engine::secure_rng;
+    use crate::generator::generator::Generator;
+    use crate::secure_mem::secure_mem::wipe_secret;
+    use rand::Rng;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    /// BIP39 english wordlist, 2048 words = 11 bits per word.
+    const WORDLIST: &str = include_str!("wordlist_en.txt");
+
+    pub const WORDS_RANGE: (u32, u32) = (3, 20);
+    pub const PIN_RANGE: (u32, u32) = (4, 12);
+    pub const TOKEN_RANGE: (u32, u32) = (8, 128);
+
+    pub const SEPARATORS: [&str; 5] = ["-", " ", ".", "_", ","];
+    pub const TOKEN_ENCODINGS: [&str; 3] = ["hex", "base64url", "base32"];
+    pub const ID_KINDS: [&str; 3] = ["uuid-v4", "ulid", "nanoid"];
+
+    const BASE64URL_CHARSET: &[u8] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    const BASE32_CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    const CROCKFORD_CHARSET: &[u8] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+    /// Result of the Analyze tab.
+    pub struct Analysis {
+        pub length: usize,
+        pub classes: Vec<(&'static str, &'static str)>,
+        pub charset_size: usize,
+        pub entropy_bits: f64,
+        pub strength: (&'static str, &'static str),
+    }
+
+    impl Generator {
+        /// Generates the secret of the current tab.
+        pub fn generate_current(&mut self) -> Result<String, (String, String)> {
+            let mut secret = match self.tab.as_str() {
+                "passphrase" => self.generate_passphrase(),
+                "pin" => self.generate_pin(),
+                "token" => self.generate_token(),
+                "identifier" => self.generate_identifier(),
+                "analyze" => Err((
+                    "Nothing to generate on the Analyze tab".to_string(),
+                    "На вкладке анализа нечего генерировать".to_string(),
+                )),
+                _ => self.generate_valid_pwd(),
+            }?;
+            // Checked after generation so invalid settings report their own error first
+            if let Err(err) = self.check_min_entropy(self.current_entropy_bits()) {
+                wipe_secret(&mut secret);
+                return Err(err);
+            }
+            Ok(secret)
+        }
+
+        /// Entropy of the secrets the current tab produces.
+        pub fn current_entropy_bits(&self) -> f64 {
+            match self.tab.as_str() {
+                "passphrase" => {
+                    let words = self.words.parse::<u32>().unwrap_or(0) as f64;
+                    let mut bits = words * (WORDLIST.lines().count() as f64).log2();
+                    if self.add_number && words > 0.0 {
+                        bits += 10f64.log2() + words.log2();
+                    }
+                    bits
+                }
+                "pin" => self.pin_len.parse::<u32>().unwrap_or(0) as f64 * 10f64.log2(),
+                "token" => self.token_bytes.parse::<u32>().unwrap_or(0) as f64 * 8.0,
+                "identifier" => match self.id_kind.as_str() {
+                    "ulid" => 80.0,
+                    "nanoid" => 126.0,
+                    _ => 122.0,
+                },
+                "analyze" => self.analyze().entropy_bits,
+                _ => self.entropy_bits(self.pwd_len.parse().unwrap_or(0)),
+            }
+        }
+
+        fn generate_passphrase(&mut self) -> Result<String, (String, String)> {
+            let words_count =
+                parse_in_range(&self.words, WORDS_RANGE, ("Words count", "Количество слов"))?;
+            let mut rng = secure_rng()?;
+            let wordlist: Vec<&str> = WORDLIST.lines().collect();
+            let mut words: Vec<String> = (0..words_count)
+                .map(|_| {
+                    let word = wordlist[rng.gen_range(0..wordlist.len())];
+                    if self.capitalize {
+                        let mut chars = word.chars();
+                        match chars.next() {
+                            Some(first) => first.to_uppercase().chain(chars).collect(),
+                            None => String::new(),
+                        }
+                    } else {
+                        word.to_string()
+                    }
+                })
+                .collect();
+            if self.add_number {
+                let idx = rng.gen_range(0..words.len());
+                let digit = rng.gen_range(0..10);
+                words[idx].push_str(&digit.to_string());
+            }
+            Ok(words.join(&self.separator))
+        }
+
+        fn generate_pin(&mut self) -> Result<String, (String, String)> {
+            let pin_len =
+                parse_in_range(&self.pin_len, PIN_RANGE, ("PIN length", "Длина PIN-кода"))?;
+            let mut rng = secure_rng()?;
+            loop {
+                let pin: Vec<u8> = (0..pin_len).map(|_| rng.gen_range(0..10u8)).collect();
+                if !self.pin_no_patterns || !is_trivial_pin(&pin) {
+                    return Ok(pin.iter().map(|d| (b'0' + d) as char).collect());
+                }
+            }
+        }
+
+        fn generate_token(&mut self) -> Result<String, (String, String)> {
+            let token_bytes = parse_in_range(
+                &self.token_bytes,
+                TOKEN_RANGE,
+                ("Token size", "Размер токена"),
+            )?;
+            let mut rng = secure_rng()?;
+            let bytes: Vec<u8> = (0..token_bytes).map(|_| rng.gen::<u8>()).collect();
+            Ok(match self.token_encoding.as_str() {
+                "base64url" => encode_bits(&bytes, 6, BASE64URL_CHARSET),
+                "base32" => encode_bits(&bytes, 5, BASE32_CHARSET),
+                _ => bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+            })
+        }
+
+        fn generate_identifier(&mut self) -> Result<String, (String, String)> {
+            let mut rng = secure_rng()?;
+            Ok(match self.id_kind.as_str() {
+                "ulid" => {
+                    let millis = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_millis() as u64)
+                        .unwrap_or(0);
+                    ulid(millis, rng.gen())
+                }
+                "nanoid" => (0..21)
+                    .map(|_| BASE64URL_CHARSET[rng.gen_range(0..BASE64URL_CHARSET.len())] as char)
+                    .collect(),
+                _ => uuid_v4(rng.gen()),
+            })
+        }
+
+        /// Estimates the strength of the password typed on the Analyze tab.
+        pub fn analyze(&self) -> Analysis {
+            let input = &self.analyze_input;
+            let mut classes: Vec<(&str, &str)> = Vec::new();
+            let mut charset_size = 0;
+            if input.chars().any(|c| c.is_ascii_lowercase()) {
+                classes.push(("a-z", "a-z"));
+                charset_size += 26;
+            }
+            if input.chars().any(|c| c.is_ascii_uppercase()) {
+                classes.push(("A-Z", "A-Z"));
+                charset_size += 26;
+            }
+            if input.chars().any(|c| c.is_ascii_digit()) {
+                classes.push(("0-9", "0-9"));
+                charset_size += 10;
+            }
+            if input.chars().any(|c| c.is_ascii_punctuation() || c == ' ') {
+                classes.push(("#$%", "#$%"));
+                charset_size += 33;
+            }
+            if !input.is_ascii() {
+                classes.push(("non-ascii", "не ascii"));
+                charset_size += 100;
+            }
+            let length = input.chars().count();
+            let entropy_bits = if charset_size > 0 {
+                length as f64 * (charset_size as f64).log2()
+            } else {
+                0.0
+            };
+            let strength = match entropy_bits {
+                e if e < 28.0 => ("very weak", "очень слабый"),
+                e if e < 36.0 => ("weak", "слабый"),
+                e if e < 60.0 => ("reasonable", "средний"),
+                e if e < 128.0 => ("strong", "сильный"),
+                _ => ("very strong", "очень сильный"),
+            };
+            Analysis {
+                length,
+                classes,
+                charset_size,
+                entropy_bits,
+                strength,
+            }
+        }
+    }
+
+    fn parse_in_range(
+        value: &str,
+        range: (u32, u32),
+        name: (&str, &str),
+    ) -> Result<u32, (String, String)> {
+        match value.parse::<u32>() {
+            Ok(val) if val >= range.0 && val <= range.1 => Ok(val),
+            _ => Err((
+                format!("{} must be between {} and {}!", name.0, range.0, range.1),
+                format!("{} должен быть от {} до {}!", name.1, range.0, range.1),
+            )),
+        }
+    }
+
+    /// 48-bit millisecond timestamp and 80 random bits in Crockford base32.
+    fn ulid(millis: u64, random: [u8; 10]) -> String {
+        let mut bytes: Vec<u8> = millis.to_be_bytes()[2..].to_vec();
+        bytes.extend(random);
+        // 128 bits are encoded as 26 symbols, the 2 leading pad bits are zero
+        let mut bits: Vec<u8> = vec![0, 0];
+        for byte in bytes {
+            bits.extend((0..8).rev().map(|i| (byte >> i) & 1));
+        }
+        bits.chunks(5)
+            .map(|chunk| {
+                let idx = chunk.iter().fold(0usize, |acc, b| (acc << 1) | *b as usize);
+                CROCKFORD_CHARSET[idx] as char
+            })
+            .collect()
+    }
+
+    /// Random bytes with the version 4 and RFC 4122 variant bits set.
+    fn uuid_v4(mut bytes: [u8; 16]) -> String {
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+        let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        format!(
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        )
+    }
+
+    /// All digits equal, or a run like 1234 / 9876.
+    fn is_trivial_pin(pin: &[u8]) -> bool {
+        let steps: Vec<i16> = pin.windows(2).map(|w| w[1] as i16 - w[0] as i16).collect();
+        steps.iter().all(|s| *s == 0)
+            || steps.iter().all(|s| *s == 1)
+            || steps.iter().all(|s| *s == -1)
+    }
+
+    /// Unpadded base32/base64url-style encoding with `bits` bits per symbol.
+    fn encode_bits(bytes: &[u8], bits: u32, charset: &[u8]) -> String {
+        let mut encoded = String::new();
+        let mut buffer: u32 = 0;
+        let mut buffered: u32 = 0;
+        for byte in bytes {
+            buffer = ((buffer << 8) | *byte as u32) & 0xffff;
+            buffered += 8;
+            while buffered >= bits {
+                buffered -= bits;
+                encoded.push(charset[((buffer >> buffered) & ((1 << bits) - 1)) as usize] as char);
+            }
+        }
+        if buffered > 0 {
+            encoded.push(
+                charset[((buffer << (bits - buffered)) & ((1 << bits) - 1)) as usize] as char,
+            );
+        }
+        encoded
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn encode_bits_matches_rfc_4648_base32() {
+            let vectors = [
+                ("", ""),
+                ("f", "MY"),
+                ("fo", "MZXQ"),
+                ("foo", "MZXW6"),
+                ("foob", "MZXW6YQ"),
+                ("fooba", "MZXW6YTB"),
+                ("foobar", "MZXW6YTBOI"),
+            ];
+            for (input, expected) in vectors {
+                assert_eq!(encode_bits(input.as_bytes(), 5, BASE32_CHARSET), expected);
+            }
+        }
+
+        #[test]
+        fn encode_bits_matches_rfc_4648_base64url() {
+            let vectors = [
+                ("", ""),
+                ("f", "Zg"),
+                ("fo", "Zm8"),
+                ("foo", "Zm9v"),
+                ("foob", "Zm9vYg"),
+                ("fooba", "Zm9vYmE"),
+                ("foobar", "Zm9vYmFy"),
+            ];
+            for (input, expected) in vectors {
+                assert_eq!(
+                    encode_bits(input.as_bytes(), 6, BASE64URL_CHARSET),
+                    expected
+                );
+            }
+            // The url-safe symbols replace `+` and `/`
+            assert_eq!(encode_bits(&[0xfb, 0xff], 6, BASE64URL_CHARSET), "-_8");
+        }
+
+        #[test]
+        fn ulid_is_26_crockford_symbols() {
+            let id = ulid(1469918176385, [0xa5; 10]);
+            assert_eq!(id.len(), 26);
+            assert!(id.bytes().all(|b| CROCKFORD_CHARSET.contains(&b)));
+            // Timestamp part of the example in the ULID specification
+            assert_eq!(&id[..10], "01ARYZ6S41");
+            assert_eq!(ulid(0, [0; 10]), "0".repeat(26));
+            assert_eq!(
+                ulid(0, [0xff; 10]),
+                format!("{}{}", "0".repeat(10), "Z".repeat(16))
+            );
+        }
+
+        #[test]
+        fn uuid_v4_sets_version_and_variant_bits() {
+            assert_eq!(uuid_v4([0; 16]), "00000000-0000-4000-8000-000000000000");
+            assert_eq!(uuid_v4([0xff; 16]), "ffffffff-ffff-4fff-bfff-ffffffffffff");
+            let id = uuid_v4([0x5a; 16]);
+            assert_eq!(id.len(), 36);
+            assert_eq!(&id[14..15], "4");
+            assert!("89ab".contains(&id[19..20]));
+        }
+
+        #[test]
+        fn trivial_pins_are_repeats_and_runs() {
+            assert!(is_trivial_pin(&[7, 7, 7, 7]));
+            assert!(is_trivial_pin(&[1, 2, 3, 4]));
+            assert!(is_trivial_pin(&[9, 8, 7, 6, 5]));
+            assert!(!is_trivial_pin(&[1, 3, 5, 7]));
+            assert!(!is_trivial_pin(&[2, 0, 2, 4]));
+            assert!(!is_trivial_pin(&[1, 2, 3, 3]));
+        }
+    }
+}
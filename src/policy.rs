@@ -0,0 +1,68 @@
+pub mod policy {
+    use crate::generator::generator::Generator;
+    use serde::Deserialize;
+    use std::env;
+    use std::fs;
+    use std::io::ErrorKind;
+    use std::path::PathBuf;
+
+    /// System-wide floors set by an administrator. Users can not go below them.
+    #[derive(Clone, Default, Deserialize)]
+    #[serde(default)]
+    pub struct AdminPolicy {
+        pub min_length: Option<u32>,
+        pub require_lowercase: bool,
+        pub require_uppercase: bool,
+        pub require_digits: bool,
+        pub require_symbols: bool,
+        pub min_entropy: Option<f64>,
+    }
+
+    impl AdminPolicy {
+        /// `/etc/passgen/policy.toml` on unix, `%ProgramData%\passgen\policy.toml` on windows.
+        pub fn path() -> Option<PathBuf> {
+            if cfg!(windows) {
+                Some(
+                    PathBuf::from(env::var_os("ProgramData")?)
+                        .join("passgen")
+                        .join("policy.toml"),
+                )
+            } else {
+                Some(PathBuf::from("/etc/passgen/policy.toml"))
+            }
+        }
+
+        pub fn load() -> Result<AdminPolicy, String> {
+            let Some(path) = AdminPolicy::path() else {
+                return Ok(AdminPolicy::default());
+            };
+            match fs::read_to_string(&path) {
+                Ok(text) => toml::from_str(&text)
+                    .map_err(|err| format!("{}: {}", path.display(), err.message())),
+                Err(err) if err.kind() == ErrorKind::NotFound => Ok(AdminPolicy::default()),
+                Err(err) => Err(format!("{}: {}", path.display(), err)),
+            }
+        }
+
+        pub fn apply_to(&self, generator: &mut Generator) {
+            if let Some(min_length) = self.min_length {
+                generator.min_pwd_len = generator.min_pwd_len.max(min_length);
+                if generator.pwd_len.parse::<u32>().unwrap_or(0) < generator.min_pwd_len {
+                    generator.pwd_len = generator.min_pwd_len.to_string();
+                    generator.cursor_to_end();
+                }
+            }
+            generator.policy = self.clone();
+        }
+
+        pub fn is_required(&self, rule_name: &str) -> bool {
+            match rule_name {
+                "letters" => self.require_lowercase,
+                "u_letters" => self.require_uppercase,
+                "numbs" => self.require_digits,
+                "spec_symbs" => self.require_symbols,
+                _ => false,
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+pub mod secure_mem {
+    /// Forbids the kernel to write a core file of this process, so a crash
+    /// (release builds abort on panic) cannot leak the current password to disk.
+    #[cfg(unix)]
+    pub fn disable_core_dumps() {
+        let no_core = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        unsafe {
+            libc::setrlimit(libc::RLIMIT_CORE, &no_core);
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            libc::prctl(libc::PR_SET_DUMPABLE, 0, 0, 0, 0);
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn disable_core_dumps() {}
+
+    /// Locked allocations by start address, and how many of them share each page.
+    /// `mlock` does not count, so a page is unlocked only when its last secret is wiped.
+    #[cfg(unix)]
+    struct LockedPages {
+        allocations: std::collections::BTreeMap<usize, usize>,
+        pages: std::collections::BTreeMap<usize, usize>,
+    }
+
+    #[cfg(unix)]
+    static LOCKED: std::sync::Mutex<LockedPages> = std::sync::Mutex::new(LockedPages {
+        allocations: std::collections::BTreeMap::new(),
+        pages: std::collections::BTreeMap::new(),
+    });
+
+    /// Pins the pages holding the secret in RAM so they are never swapped out.
+    pub fn lock_secret(secret: &str) {
+        #[cfg(unix)]
+        lock_region(secret.as_ptr() as usize, secret.len());
+        #[cfg(not(unix))]
+        let _ = secret;
+    }
+
+    /// Pins the whole capacity of a buffer before the secret is written into it.
+    pub fn lock_buffer(buffer: &Vec<u8>) {
+        #[cfg(unix)]
+        lock_region(buffer.as_ptr() as usize, buffer.capacity());
+        #[cfg(not(unix))]
+        let _ = buffer;
+    }
+
+    /// Overwrites the secret with zeroes and releases its pages lock.
+    pub fn wipe_secret(secret: &mut String) {
+        let bytes = unsafe { secret.as_mut_vec() };
+        for byte in bytes.iter_mut() {
+            unsafe { std::ptr::write_volatile(byte, 0) };
+        }
+        std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+        #[cfg(unix)]
+        unlock_region(secret.as_ptr() as usize);
+        secret.clear();
+    }
+
+    #[cfg(unix)]
+    fn lock_region(start: usize, len: usize) {
+        if len == 0 {
+            return;
+        }
+        let mut locked = LOCKED.lock().unwrap_or_else(|err| err.into_inner());
+        match locked.allocations.get(&start) {
+            Some(locked_len) if *locked_len == len => return,
+            Some(_) => release(&mut locked, start),
+            None => {}
+        }
+        locked.allocations.insert(start, len);
+        for page in pages(start, len) {
+            let count = locked.pages.entry(page).or_insert(0);
+            *count += 1;
+            if *count == 1 {
+                unsafe {
+                    libc::mlock(page as *const libc::c_void, page_size());
+                }
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    fn unlock_region(start: usize) {
+        let mut locked = LOCKED.lock().unwrap_or_else(|err| err.into_inner());
+        release(&mut locked, start);
+    }
+
+    #[cfg(unix)]
+    fn release(locked: &mut LockedPages, start: usize) {
+        let Some(len) = locked.allocations.remove(&start) else {
+            return;
+        };
+        for page in pages(start, len) {
+            if let Some(count) = locked.pages.get_mut(&page) {
+                *count -= 1;
+                if *count == 0 {
+                    locked.pages.remove(&page);
+                    unsafe {
+                        libc::munlock(page as *const libc::c_void, page_size());
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    fn pages(start: usize, len: usize) -> impl Iterator<Item = usize> {
+        let page_size = page_size();
+        let first = start - start % page_size;
+        (first..start + len).step_by(page_size)
+    }
+
+    #[cfg(unix)]
+    fn page_size() -> usize {
+        unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+    }
+}
@@ -0,0 +1,47 @@
+pub mod template {
+    use crate::generator::generator::Generator;
+
+    impl Generator {
+        /// Fills the user output template, e.g. `DB_PASSWORD="{password}"`.
+        /// Without a template the bare password is returned.
+        pub fn render_template(&self, pwd: &str) -> String {
+            self.fill_template(
+                pwd,
+                &self.label,
+                self.template_policy(),
+                self.template_entropy(pwd),
+            )
+        }
+
+        /// The `{entropy}` value of a secret made with the current settings.
+        pub fn template_entropy(&self, pwd: &str) -> f64 {
+            if self.tab == "password" {
+                self.entropy_bits(pwd.chars().count() as u32)
+            } else {
+                self.current_entropy_bits()
+            }
+        }
+
+        /// The `{policy}` value: the active preset name or `custom`.
+        pub fn template_policy(&self) -> &str {
+            if self.active_preset.is_empty() {
+                "custom"
+            } else {
+                self.active_preset.as_str()
+            }
+        }
+
+        /// Fills the template with the given values, for secrets generated earlier.
+        pub fn fill_template(&self, pwd: &str, label: &str, policy: &str, entropy: f64) -> String {
+            let Some(template) = self.template.as_deref().filter(|t| !t.is_empty()) else {
+                return pwd.to_string();
+            };
+            template
+                .replace("{label}", label)
+                .replace("{length}", &pwd.chars().count().to_string())
+                .replace("{policy}", policy)
+                .replace("{entropy}", &format!("{:.0}", entropy))
+                .replace("{password}", pwd)
+        }
+    }
+}
@@ -0,0 +1,83 @@
+pub mod theme {
+    use ratatui::style::{Color, Modifier, Style};
+
+    /// Color themes, chosen with the `theme` config key or `PASSGEN_THEME`.
+    pub const THEMES: [&str; 4] = ["dark", "light", "high-contrast", "mono"];
+
+    /// Styles of the interface parts.
+    pub struct Theme {
+        pub base: Style,
+        pub border: Style,
+        pub title: Style,
+        pub legend: Style,
+        /// Focused field, selected row and active tab.
+        pub focus: Style,
+        /// Rules locked by the administrator policy.
+        pub disabled: Style,
+        pub muted: Style,
+        pub result: Style,
+        pub result_status: Style,
+        pub lang_active: Style,
+        pub lang_inactive: Style,
+    }
+
+    pub fn theme_for(name: &str) -> Theme {
+        match name {
+            "light" => Theme {
+                base: Style::new().fg(Color::Black).bg(Color::White),
+                border: Style::new().fg(Color::DarkGray).bg(Color::White),
+                title: Style::new().fg(Color::Blue).bg(Color::White),
+                legend: Style::new().fg(Color::Green).bg(Color::White),
+                focus: Style::new().fg(Color::Blue).add_modifier(Modifier::BOLD),
+                disabled: Style::new().fg(Color::Gray),
+                muted: Style::new().fg(Color::DarkGray),
+                result: Style::new().fg(Color::White).bg(Color::Blue),
+                result_status: Style::new().fg(Color::Black).bg(Color::Gray),
+                lang_active: Style::new().fg(Color::White).bg(Color::Blue),
+                lang_inactive: Style::new().fg(Color::Black).bg(Color::Gray),
+            },
+            "high-contrast" => Theme {
+                base: Style::new().fg(Color::White).bg(Color::Black),
+                border: Style::new().fg(Color::White).bg(Color::Black),
+                title: Style::new().fg(Color::White).bg(Color::Black),
+                legend: Style::new().fg(Color::White).bg(Color::Black),
+                focus: Style::new()
+                    .fg(Color::Black)
+                    .bg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+                disabled: Style::new().fg(Color::Gray),
+                muted: Style::new().fg(Color::White),
+                result: Style::new().fg(Color::Black).bg(Color::White),
+                result_status: Style::new().fg(Color::White).bg(Color::Black),
+                lang_active: Style::new().fg(Color::Black).bg(Color::White),
+                lang_inactive: Style::new().fg(Color::White).bg(Color::Black),
+            },
+            "mono" => Theme {
+                base: Style::new(),
+                border: Style::new(),
+                title: Style::new(),
+                legend: Style::new(),
+                focus: Style::new().add_modifier(Modifier::REVERSED),
+                disabled: Style::new().add_modifier(Modifier::DIM),
+                muted: Style::new(),
+                result: Style::new(),
+                result_status: Style::new(),
+                lang_active: Style::new().add_modifier(Modifier::REVERSED),
+                lang_inactive: Style::new(),
+            },
+            _ => Theme {
+                base: Style::new().fg(Color::White).bg(Color::Black),
+                border: Style::new().fg(Color::Gray).bg(Color::Black),
+                title: Style::new().fg(Color::Cyan).bg(Color::Black),
+                legend: Style::new().fg(Color::LightGreen).bg(Color::Black),
+                focus: Style::new().fg(Color::Yellow),
+                disabled: Style::new().fg(Color::DarkGray),
+                muted: Style::new().fg(Color::Gray),
+                result: Style::new().fg(Color::Black).bg(Color::White),
+                result_status: Style::new().bg(Color::Gray),
+                lang_active: Style::new().fg(Color::White).bg(Color::Blue),
+                lang_inactive: Style::new().fg(Color::Black).bg(Color::Gray),
+            },
+        }
+    }
+}
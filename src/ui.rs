@@ -1,255 +1,1505 @@
 pub mod ui {
-    use crate::generator::generator::Generator;
+    use crate::escape::escape::{escape_for, view_title, ESCAPE_VIEWS};
+    use crate::generator::generator::{Generator, TABS};
+    use crate::keymap::keymap::Action;
+    use crate::secure_mem::secure_mem::wipe_secret;
+    use crate::theme::theme::{theme_for, Theme};
     use ratatui::layout::Alignment::Center;
-    use ratatui::layout::{Alignment, Flex, Layout, Position, Rect};
+    use ratatui::layout::{Alignment, Flex, Layout, Margin, Position, Rect};
     use ratatui::prelude::{Line, Modifier, Span, Style, Stylize};
-    use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+    use ratatui::widgets::{Block, BorderType, Borders, Clear, Paragraph, Tabs, Wrap};
     use ratatui::Frame;
+    use unicode_width::UnicodeWidthStr;
 
-    pub fn draw_ui(f: &mut Frame, generator: &Generator) {
-        if f.area().height < 40 {
-            // Expand check
-            let expand_text = format!(
-                "▲▼ Please expand the window to accommodate the contents / Пожалуйста, разверните окно для вмещения содержимого ▲▼ {}/40",
-                f.area().height
-            );
-            f.render_widget(
-                Paragraph::new(expand_text.to_string())
-                    .light_green()
-                    .on_black()
-                    .alignment(Alignment::Center),
-                centered_rect(
-                    Rect::new(0, 0, f.area().width, 1),
-                    expand_text.len() as u16,
-                    1,
-                ),
-            );
-        } else {
-            // Main block
-            let main_block = Block::default()
+    /// Where the parts of the interface go on the current terminal.
+    struct Screen {
+        main: Rect,
+        /// 3 rows for the boxed title, 1 for a plain line, 0 when hidden.
+        title: Rect,
+        /// 3 lines, 1 short line or hidden.
+        legend: Rect,
+        tabs: Rect,
+        form: Rect,
+        /// Fields in bordered boxes of 3 rows, or single-line rows on small terminals.
+        boxed: bool,
+        /// 6 rows with spacing, 4 or less when compact.
+        result: Rect,
+        status: Rect,
+        footer: Rect,
+    }
+
+    pub fn draw_ui(f: &mut Frame, generator: &mut Generator) {
+        let theme = current_theme(generator);
+        generator.click_areas.clear();
+        let screen = screen_layout(f.area(), generator);
+
+        // Main block
+        let main_block = if screen.main.height >= 12 && screen.main.width >= 24 {
+            Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().gray().on_black())
+                .border_style(theme.border)
                 .border_type(BorderType::Double)
-                .on_black();
-            f.render_widget(
-                main_block,
-                centered_rect(Rect::new(0, 0, f.area().width, 40), 70, 40),
-            );
+                .style(theme.base)
+        } else {
+            Block::default().style(theme.base)
+        };
+        f.render_widget(main_block, screen.main);
 
-            // Title
-            let par = Paragraph::new("MAMMOTHCODING PASSGEN")
-                .add_modifier(Modifier::BOLD)
-                .block(
+        // Title
+        let par = Paragraph::new("MAMMOTHCODING PASSGEN")
+            .style(theme.title)
+            .add_modifier(Modifier::BOLD)
+            .alignment(Alignment::Center);
+        if screen.title.height >= 3 {
+            f.render_widget(
+                par.block(
                     Block::default()
                         .borders(Borders::ALL)
                         .border_type(BorderType::Rounded),
-                )
-                .cyan()
-                .on_black()
-                .alignment(Alignment::Center);
-            f.render_widget(
-                par,
-                centered_rect(Rect::new(0, 1, f.area().width, 3), 60, 3),
+                ),
+                screen.title,
             );
+        } else {
+            f.render_widget(par, screen.title);
+        }
+
+        // Legend
+        let par = Paragraph::new(legend_lines(generator, screen.legend.height >= 3))
+            .style(theme.legend)
+            .alignment(Alignment::Center);
+        f.render_widget(par, screen.legend);
 
-            // Legend
-            let legend = if generator.lang.as_str() == "en" {
+        // Clipboard indicator
+        let clip = if generator.lang.as_str() == "en" {
+            format!(
+                "Copy to: {} | auto-copy: {}",
+                clipboard_target_name(generator),
+                if generator.auto_copy { "on" } else { "off" }
+            )
+        } else {
+            format!(
+                "Буфер: {} | автокопирование: {}",
+                clipboard_target_name(generator),
+                if generator.auto_copy {
+                    "вкл"
+                } else {
+                    "выкл"
+                }
+            )
+        };
+        f.render_widget(
+            Paragraph::new(clip)
+                .style(theme.legend)
+                .alignment(Alignment::Left),
+            Rect::new(
+                screen.status.x + 2,
+                screen.status.y,
+                screen.status.width.saturating_sub(6),
+                screen.status.height,
+            ),
+        );
+
+        // Lang indicator
+        let ind = if generator.lang.as_str() == "en" {
+            Line::from(vec![
+                Span::raw("En").style(theme.lang_active).bold(),
+                Span::raw("Ru").style(theme.lang_inactive),
+            ])
+        } else {
+            Line::from(vec![
+                Span::raw("En").style(theme.lang_inactive),
+                Span::raw("Ru").style(theme.lang_active).bold(),
+            ])
+        };
+        f.render_widget(
+            Paragraph::new(ind).alignment(Alignment::Right),
+            screen.status,
+        );
+
+        // Tabs
+        let mut titles: Vec<&str> = TABS
+            .iter()
+            .map(|tab| tab_title(tab, &generator.lang))
+            .collect();
+        let mut selected = TABS.iter().position(|&t| t == generator.tab).unwrap_or(0);
+        let mut tab_names: Vec<String> = TABS.iter().map(|tab| tab.to_string()).collect();
+        let tabs_width: u16 = titles.iter().map(|title| title.width() as u16 + 3).sum();
+        if tabs_width > screen.tabs.width + 1 {
+            // Too narrow for all the titles: only the current one between arrows
+            let prev = TABS[(selected + TABS.len() - 1) % TABS.len()];
+            let next = TABS[(selected + 1) % TABS.len()];
+            titles = vec!["◂", titles[selected], "▸"];
+            tab_names = vec![prev.to_string(), generator.tab.clone(), next.to_string()];
+            selected = 1;
+        }
+        // Each title is padded by a space on both sides and followed by a divider
+        let mut tab_x = screen.tabs.x;
+        for (tab, title) in tab_names.iter().zip(titles.iter()) {
+            let width = title.width() as u16 + 2;
+            generator.click_areas.push((
+                format!("tab:{}", tab),
+                Rect::new(tab_x, screen.tabs.y, width, screen.tabs.height)
+                    .intersection(screen.tabs),
+            ));
+            tab_x += width + 1;
+        }
+        f.render_widget(
+            Tabs::new(titles)
+                .select(selected)
+                .style(theme.base)
+                .highlight_style(theme.focus.add_modifier(Modifier::BOLD)),
+            screen.tabs,
+        );
+
+        draw_form(f, generator, screen.form, screen.boxed);
+
+        // Password result area
+        let result_width = screen.result.width.saturating_sub(4) as usize;
+        // The password is borrowed, not copied, so no unwiped clone is left per frame
+        let pwd = if generator.pwd_idle_hidden {
+            Line::from(if generator.lang.as_str() == "en" {
+                "•••••• password hidden ••••••"
+            } else {
+                "•••••• пароль скрыт ••••••"
+            })
+        } else if generator.focus_lost {
+            Line::from(if generator.lang.as_str() == "en" {
+                "•••••• hidden while the window is inactive ••••••"
+            } else {
+                "•••••• скрыт, пока окно неактивно ••••••"
+            })
+        } else if generator.pwd_masked {
+            Line::from("•".repeat(generator.pwd.chars().count().min(result_width)))
+        } else if generator.pwd.chars().count() > result_width {
+            Line::from(vec![
+                Span::raw(char_prefix(&generator.pwd, result_width.saturating_sub(3))),
+                Span::raw("..."),
+            ])
+        } else {
+            Line::from(generator.pwd.as_str())
+        };
+        if (!generator.pwd.is_empty()
+            || generator.pwd_idle_hidden
+            || !generator.errors.0.is_empty())
+            && screen.result.height > 0
+        {
+            let text: String = if generator.lang.as_str() == "en" {
+                if generator.pwd_idle_hidden {
+                    format!(
+                        "wiped after {} s idle, press Enter for a new one",
+                        generator.idle_timeout_secs
+                    )
+                } else if !generator.errors.0.is_empty() {
+                    generator.errors.0.clone()
+                } else if generator.pick_mode {
+                    "press Enter to accept or F5 to regenerate".to_string()
+                } else if generator.pwd_copied {
+                    format!(
+                        "this password was copied to {}{}",
+                        clipboard_target_name(generator),
+                        notice_suffix(&generator.notice.0)
+                    )
+                } else {
+                    format!(
+                        "press F2 to copy this password{}",
+                        notice_suffix(&generator.notice.0)
+                    )
+                }
+            } else if generator.pwd_idle_hidden {
+                format!(
+                    "стёрт после {} с бездействия, Enter для нового",
+                    generator.idle_timeout_secs
+                )
+            } else if !generator.errors.1.is_empty() {
+                generator.errors.1.clone()
+            } else if generator.pick_mode {
+                "Enter для выбора или F5 для новой генерации".to_string()
+            } else if generator.pwd_copied {
+                format!(
+                    "пароль был скопирован в {}{}",
+                    clipboard_target_name(generator),
+                    notice_suffix(&generator.notice.1)
+                )
+            } else {
+                format!(
+                    "нажмите F2 для копирования пароля{}",
+                    notice_suffix(&generator.notice.1)
+                )
+            };
+            let pwd_text = if screen.result.height >= 6 {
                 vec![
-                    Line::from(Span::raw("Press ")),
-                    Line::from(vec![
-                        Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
-                        Span::raw(" to exit, "),
-                        Span::styled("F9", Style::default().add_modifier(Modifier::BOLD)),
-                        Span::raw(" to switch interface lang, "),
-                    ]),
-                    Line::from(vec![
-                        Span::styled(
-                            "Tab, ▲, ▼, Space",
-                            Style::default().add_modifier(Modifier::BOLD),
-                        ),
-                        Span::raw(" to manage a rules"),
-                    ]),
-                    Line::from(vec![
-                        Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
-                        Span::raw(" to generate a password"),
-                    ]),
+                    Line::from(Span::raw("")),
+                    pwd.bold(),
+                    Line::from(Span::raw("")),
+                    Line::from(Span::raw(text).style(theme.result_status).italic()),
                 ]
             } else {
                 vec![
-                    Line::from(Span::raw("Нажмите ")),
-                    Line::from(vec![
-                        Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
-                        Span::raw(" для выхода, "),
-                        Span::styled("F9", Style::default().add_modifier(Modifier::BOLD)),
-                        Span::raw(" для переключения языка интерфейса, "),
-                    ]),
-                    Line::from(vec![
-                        Span::styled(
-                            "Tab, ▲, ▼, Space",
-                            Style::default().add_modifier(Modifier::BOLD),
-                        ),
-                        Span::raw(" для выбора правил"),
-                    ]),
-                    Line::from(vec![
-                        Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
-                        Span::raw(" для генерации пароля"),
-                    ]),
+                    pwd.bold(),
+                    Line::from(Span::raw(text).style(theme.result_status).italic()),
                 ]
             };
-            let par = Paragraph::new(legend)
-                .block(Block::new().title(""))
-                .style(Style::new().light_green().on_black())
+            let pwd_title = match (generator.tab.as_str(), generator.lang.as_str()) {
+                ("passphrase", "en") => "Passphrase is",
+                ("passphrase", _) => "Ваша фраза",
+                ("pin", "en") => "PIN is",
+                ("pin", _) => "Ваш PIN-код",
+                ("token", "en") => "Token is",
+                ("token", _) => "Ваш токен",
+                ("identifier", "en") => "Identifier is",
+                ("identifier", _) => "Ваш идентификатор",
+                (_, "en") => "Password is",
+                _ => "Ваш пароль",
+            };
+            let mut par = Paragraph::new(pwd_text)
+                .style(theme.result)
                 .alignment(Alignment::Center);
-            f.render_widget(
-                par,
-                centered_rect(Rect::new(0, 4, f.area().width, 7), 60, 7),
-            );
+            if screen.result.height >= 3 {
+                par = par.block(
+                    Block::new()
+                        .title(pwd_title)
+                        .title_alignment(Center)
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .style(theme.result),
+                );
+            }
+            f.render_widget(par, screen.result);
+            generator
+                .click_areas
+                .push(("result".to_string(), screen.result));
+        }
 
-            // Lang indicator
-            let ind = if generator.lang.as_str() == "en" {
+        // Footer
+        let ver = env!("CARGO_PKG_VERSION");
+        let footer = if generator.lang.as_str() == "en" {
+            format!("Dev on Rust | v{ver} | 2024 | https://passgen.mamont.xyz")
+        } else {
+            format!("Создано на языке Rust | v{ver} | 2024 | https://passgen.mamont.xyz")
+        };
+        let par = Paragraph::new(footer)
+            .block(Block::default().borders(Borders::TOP).style(theme.border))
+            .style(theme.title)
+            .alignment(Alignment::Center);
+        f.render_widget(par, screen.footer);
+
+        if generator.presets_popup {
+            draw_presets_popup(f, generator);
+        }
+        if generator.escape_popup {
+            draw_escape_popup(f, generator);
+        }
+        if generator.viewer_popup {
+            draw_viewer_popup(f, generator);
+        }
+        if generator.history_panel {
+            draw_history_panel(f, generator);
+        }
+        if generator.help_popup {
+            draw_help_popup(f, generator);
+        }
+        if generator.quit_popup {
+            draw_quit_popup(f, generator);
+        }
+    }
+
+    /// The 70x40 screen the interface was designed for, or a compact one that
+    /// drops decorations, puts fields on single lines and scrolls them when needed.
+    fn screen_layout(area: Rect, generator: &Generator) -> Screen {
+        if area.height >= 40 && area.width >= 70 {
+            return Screen {
+                main: centered_rect(Rect::new(0, 0, area.width, 40), 70, 40),
+                title: centered_rect(Rect::new(0, 1, area.width, 3), 60, 3),
+                legend: centered_rect(Rect::new(0, 6, area.width, 3), 60, 3),
+                tabs: centered_rect(Rect::new(0, 10, area.width, 1), 64, 1),
+                form: centered_rect(Rect::new(0, 11, area.width, 19), 44, 19),
+                boxed: true,
+                result: centered_rect(Rect::new(0, 30, area.width, 6), 55, 6),
+                status: centered_rect(Rect::new(0, 36, area.width, 1), 68, 1),
+                footer: centered_rect(Rect::new(0, 37, area.width, 2), 68, 2),
+            };
+        }
+
+        let main = centered_rect(area, area.width.min(70), area.height);
+        let inner = if main.height >= 12 && main.width >= 24 {
+            main.inner(Margin::new(1, 1))
+        } else {
+            main
+        };
+        let mut rows = inner.height;
+        let tabs_height = rows.min(1);
+        rows -= tabs_height;
+        let status_height = if rows >= 6 { 1 } else { 0 };
+        rows -= status_height;
+        let result_height = if rows >= 6 { 4 } else { rows.min(3) };
+        rows -= result_height;
+        let boxed = rows >= form_height(generator, true) + 2 && inner.width >= 30;
+        let mut spare = rows.saturating_sub(form_height(generator, boxed));
+        let title_height = spare.min(1);
+        spare -= title_height;
+        let legend_height = match spare {
+            0 => 0,
+            5.. if inner.width >= 60 => 3,
+            _ => 1,
+        };
+        let form_height = rows - title_height - legend_height;
+
+        let centered = |y: u16, width: u16, height: u16| {
+            let width = width.min(inner.width);
+            Rect::new(inner.x + (inner.width - width) / 2, y, width, height)
+        };
+        let mut y = inner.y;
+        let title = centered(y, inner.width, title_height);
+        y += title_height;
+        let legend = centered(y, inner.width, legend_height);
+        y += legend_height;
+        let tabs = centered(y, 64, tabs_height);
+        y += tabs_height;
+        let form = centered(y, 44, form_height);
+        y += form_height;
+        let result = centered(y, 55, result_height);
+        y += result_height;
+        let status = centered(y, 68, status_height);
+        Screen {
+            main,
+            title,
+            legend,
+            tabs,
+            form,
+            boxed,
+            result,
+            status,
+            footer: Rect::new(inner.x, y, 0, 0),
+        }
+    }
+
+    fn legend_lines(generator: &Generator, full: bool) -> Vec<Line<'static>> {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let en = generator.lang.as_str() == "en";
+        let key = |action: Action| {
+            let names = generator.keymap.key_names(&[action]);
+            Span::styled(names.first().cloned().unwrap_or("-".to_string()), bold)
+        };
+        if !full {
+            return vec![Line::from(vec![
+                key(Action::Help),
+                Span::raw(if en { " help, " } else { " справка, " }),
+                key(Action::Generate),
+                Span::raw(if en {
+                    " generate, "
+                } else {
+                    " генерация, "
+                }),
+                key(Action::Copy),
+                Span::raw(if en { " copy, " } else { " копия, " }),
+                key(Action::Quit),
+                Span::raw(if en { " exit" } else { " выход" }),
+            ])];
+        }
+        let mut help = vec![Span::raw(if en { "Press " } else { "Нажмите " })];
+        for (idx, name) in generator
+            .keymap
+            .key_names(&[Action::Help])
+            .into_iter()
+            .take(2)
+            .enumerate()
+        {
+            if idx > 0 {
+                help.push(Span::raw(if en { " or " } else { " или " }));
+            }
+            help.push(Span::styled(name, bold));
+        }
+        help.push(Span::raw(if en {
+            " for help on all keys and rules"
+        } else {
+            " для справки по клавишам и правилам"
+        }));
+        // "Ctrl+◀" and "Ctrl+▶" are shown together as "Ctrl+◀▶"
+        let tabs = match (
+            generator.keymap.key_names(&[Action::PrevTab]).first(),
+            generator.keymap.key_names(&[Action::NextTab]).first(),
+        ) {
+            (Some(prev), Some(next)) => match (prev.rsplit_once('+'), next.rsplit_once('+')) {
+                (Some((prev_mods, prev_key)), Some((next_mods, next_key)))
+                    if prev_mods == next_mods =>
+                {
+                    format!("{}+{}{}", prev_mods, prev_key, next_key)
+                }
+                _ => format!("{}/{}", prev, next),
+            },
+            _ => "-".to_string(),
+        };
+        if en {
+            vec![
+                Line::from(help),
                 Line::from(vec![
-                    Span::raw("En").bold().white().on_blue(),
-                    Span::raw("Ru").black().on_gray(),
-                ])
-            } else {
+                    key(Action::Generate),
+                    Span::raw(" to generate, "),
+                    key(Action::Copy),
+                    Span::raw(" to copy, "),
+                    key(Action::Quit),
+                    Span::raw(" to exit"),
+                ]),
                 Line::from(vec![
-                    Span::raw("En").black().on_gray(),
-                    Span::raw("Ru").bold().white().on_blue(),
-                ])
+                    Span::styled(tabs, bold),
+                    Span::raw(" to switch tabs, "),
+                    key(Action::Lang),
+                    Span::raw(" to switch interface lang"),
+                ]),
+            ]
+        } else {
+            vec![
+                Line::from(help),
+                Line::from(vec![
+                    key(Action::Generate),
+                    Span::raw(" для генерации, "),
+                    key(Action::Copy),
+                    Span::raw(" для копирования, "),
+                    key(Action::Quit),
+                    Span::raw(" для выхода"),
+                ]),
+                Line::from(vec![
+                    Span::styled(tabs, bold),
+                    Span::raw(" для вкладок, "),
+                    key(Action::Lang),
+                    Span::raw(" для переключения языка"),
+                ]),
+            ]
+        }
+    }
+
+    /// A field of the current tab as it is drawn.
+    enum FormRow {
+        Input {
+            field: &'static str,
+            title: String,
+            value: String,
+        },
+        Toggle {
+            field: &'static str,
+            label: String,
+            required: bool,
+        },
+        Choice {
+            field: &'static str,
+            label: String,
+            value: String,
+        },
+    }
+
+    impl FormRow {
+        fn field(&self) -> &'static str {
+            match self {
+                FormRow::Input { field, .. }
+                | FormRow::Toggle { field, .. }
+                | FormRow::Choice { field, .. } => field,
+            }
+        }
+    }
+
+    fn form_rows(generator: &Generator) -> Vec<FormRow> {
+        let en = generator.lang.as_str() == "en";
+        generator
+            .tab_fields()
+            .iter()
+            .map(|&field| {
+                let label = field_label(field, en);
+                if let Some(value) = choice_value(generator, field) {
+                    FormRow::Choice {
+                        field,
+                        label,
+                        value: value.to_string(),
+                    }
+                } else if field == "analyze_input" {
+                    FormRow::Input {
+                        field,
+                        title: label,
+                        value: "•".repeat(generator.analyze_input.chars().count()),
+                    }
+                } else if let Some(value) = field_value(generator, field) {
+                    let title = match generator.field_range(field) {
+                        Some(range) => range_title(&label, range),
+                        None => label,
+                    };
+                    FormRow::Input {
+                        field,
+                        title,
+                        value: value.to_string(),
+                    }
+                } else {
+                    FormRow::Toggle {
+                        field,
+                        label,
+                        required: generator.policy.is_required(field),
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Rows the form of the current tab needs.
+    fn form_height(generator: &Generator, boxed: bool) -> u16 {
+        let fields = generator.tab_fields().len() as u16;
+        match (generator.tab == "analyze", boxed) {
+            (true, true) => fields * 3 + 8,
+            (true, false) => fields + 5,
+            (false, true) => fields * 3,
+            (false, false) => fields,
+        }
+    }
+
+    /// Fields of the current tab, scrolled to keep the focused one visible.
+    fn draw_form(f: &mut Frame, generator: &mut Generator, area: Rect, boxed: bool) {
+        let theme = current_theme(generator);
+        let rows = form_rows(generator);
+        let row_height = if boxed { 3 } else { 1 };
+        let visible = (area.height / row_height).max(1) as usize;
+        let focused = rows
+            .iter()
+            .position(|row| row.field() == generator.rules_position)
+            .unwrap_or(0);
+        let first = focused.saturating_sub(visible - 1);
+        let no_popup = !generator.presets_popup
+            && !generator.escape_popup
+            && !generator.history_panel
+            && !generator.help_popup;
+
+        for (idx, row) in rows.iter().enumerate().skip(first).take(visible) {
+            let y = area.y + (idx - first) as u16 * row_height;
+            let row_area = Rect::new(area.x, y, area.width, row_height).intersection(area);
+            let field = row.field();
+            let is_focused = generator.rules_position == field;
+            let (par, text_x) = match row {
+                FormRow::Input { title, value, .. } if boxed => (
+                    Paragraph::new(value.as_str()).block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title(title.as_str())
+                            .title_alignment(Center),
+                    ),
+                    row_area.x + 1,
+                ),
+                FormRow::Input { title, value, .. } => {
+                    let prefix = format!("{}: ", title);
+                    let text_x = row_area.x + prefix.width() as u16;
+                    (
+                        Paragraph::new(Line::from(vec![
+                            Span::raw(prefix),
+                            Span::raw(value.as_str()).bold(),
+                        ])),
+                        text_x,
+                    )
+                }
+                FormRow::Toggle {
+                    label, required, ..
+                } => {
+                    let on_criteria = if generator.get_rule_state(field) {
+                        "+"
+                    } else {
+                        "-"
+                    };
+                    let policy_mark = match (required, generator.lang.as_str()) {
+                        (false, _) => "",
+                        (true, "en") => " (policy)",
+                        (true, _) => " (политика)",
+                    };
+                    (
+                        Paragraph::new(format!(" {}   {}{}", on_criteria, label, policy_mark)),
+                        row_area.x,
+                    )
+                }
+                FormRow::Choice { label, value, .. } => (
+                    Paragraph::new(format!(" ◂ {} ▸   {}", value, label)),
+                    row_area.x,
+                ),
+            };
+            let mut par = if boxed && !matches!(row, FormRow::Input { .. }) {
+                par.block(Block::default().borders(Borders::ALL))
+            } else {
+                par
+            };
+            par = if is_focused {
+                par.style(theme.focus)
+            } else if matches!(row, FormRow::Toggle { required: true, .. }) {
+                par.style(theme.disabled)
+            } else {
+                par.style(theme.base)
             };
+            f.render_widget(par, row_area);
+            generator.click_areas.push((field.to_string(), row_area));
+
+            if let FormRow::Input { .. } = row {
+                // Clicks on the value place the cursor under the mouse
+                let value_area = Rect::new(
+                    text_x.saturating_sub(1),
+                    row_area.y,
+                    row_area.right().saturating_sub(text_x.saturating_sub(1)),
+                    row_area.height,
+                );
+                generator.click_areas.push((field.to_string(), value_area));
+                let cursor_y = if boxed { row_area.y + 1 } else { row_area.y };
+                if is_focused && no_popup && cursor_y < row_area.bottom() {
+                    f.set_cursor_position(Position::from((
+                        text_x + generator.cursor_position as u16,
+                        cursor_y,
+                    )));
+                }
+            }
+        }
+
+        // Scroll marks
+        if first > 0 {
+            f.render_widget(
+                Paragraph::new("▲").alignment(Alignment::Right),
+                Rect::new(area.x, area.y, area.width, 1),
+            );
+        }
+        if first + visible < rows.len() {
             f.render_widget(
-                Paragraph::new(ind).alignment(Alignment::Right),
-                centered_rect(Rect::new(0, 36, f.area().width, 1), 68, 1),
+                Paragraph::new("▼").alignment(Alignment::Right),
+                Rect::new(area.x, area.bottom() - 1, area.width, 1),
             );
+        }
+
+        if generator.tab == "analyze" {
+            let stats_y = area.y + row_height + if boxed { 1 } else { 0 };
+            let stats_area = Rect::new(
+                area.x,
+                stats_y,
+                area.width,
+                area.bottom().saturating_sub(stats_y),
+            );
+            draw_analysis(f, generator, stats_area, boxed);
+        }
+    }
 
-            // Password length input area
-            let pwd_len_field_area = centered_rect(Rect::new(0, 11, f.area().width, 3), 44, 3);
-            let mut pwd_len_field = Paragraph::new(generator.pwd_len.as_str()).block(
+    fn draw_analysis(f: &mut Frame, generator: &Generator, area: Rect, boxed: bool) {
+        let theme = current_theme(generator);
+        let en = generator.lang.as_str() == "en";
+        let analysis = generator.analyze();
+        let classes: Vec<&str> = analysis
+            .classes
+            .iter()
+            .map(|class| if en { class.0 } else { class.1 })
+            .collect();
+        let none = if en { "none" } else { "нет" };
+        let lines = vec![
+            Line::from(format!(
+                "{}: {}",
+                if en { "Length" } else { "Длина" },
+                analysis.length
+            )),
+            Line::from(format!(
+                "{}: {}",
+                if en { "Classes" } else { "Классы" },
+                if classes.is_empty() {
+                    none.to_string()
+                } else {
+                    classes.join(", ")
+                }
+            )),
+            Line::from(format!(
+                "{}: {}",
+                if en {
+                    "Charset size"
+                } else {
+                    "Размер алфавита"
+                },
+                analysis.charset_size
+            )),
+            Line::from(format!(
+                "{}: {:.0} {}",
+                if en { "Entropy" } else { "Энтропия" },
+                analysis.entropy_bits,
+                if en { "bits" } else { "бит" }
+            )),
+            Line::from(vec![
+                Span::raw(if en {
+                    "Strength: "
+                } else {
+                    "Стойкость: "
+                }),
+                Span::raw(if en {
+                    analysis.strength.0
+                } else {
+                    analysis.strength.1
+                })
+                .bold(),
+            ]),
+        ];
+        let mut par = Paragraph::new(lines).style(theme.base);
+        if boxed {
+            par = par.block(
                 Block::default()
+                    .title(if en { "Estimate" } else { "Оценка" })
+                    .title_alignment(Center)
                     .borders(Borders::ALL)
-                    .title(format!(
-                        "{} ({} - {})",
-                        if generator.lang.as_str() == "en" {
-                            "Password length"
-                        } else {
-                            "Длина пароля"
-                        },
-                        generator.min_pwd_len,
-                        generator.max_pwd_len
-                    ))
-                    .title_alignment(Center),
+                    .border_type(BorderType::Rounded),
             );
-            pwd_len_field = if generator.rules_position == "pwd_len" {
-                pwd_len_field.yellow()
+        }
+        let height = if boxed { 7 } else { 5 };
+        f.render_widget(
+            par,
+            Rect::new(area.x, area.y, area.width, area.height.min(height)),
+        );
+    }
+
+    fn field_label(field: &str, en: bool) -> String {
+        let label = match (field, en) {
+            ("pwd_len", true) => "Password length",
+            ("pwd_len", false) => "Длина пароля",
+            ("letters", true) => "include lowercase letters",
+            ("letters", false) => "включая маленькие буквы",
+            ("u_letters", true) => "include capital letters",
+            ("u_letters", false) => "включая большие буквы",
+            ("numbs", true) => "include numbers",
+            ("numbs", false) => "включая цифры",
+            ("spec_symbs", true) => "include special symbols",
+            ("spec_symbs", false) => "включая спец. символы",
+            ("convenience_criterion", true) => "strong & usability password",
+            ("convenience_criterion", false) => "сильный и удобный пароль",
+            ("words", true) => "Words",
+            ("words", false) => "Количество слов",
+            ("separator", true) => "separator",
+            ("separator", false) => "разделитель",
+            ("capitalize", true) => "capitalize words",
+            ("capitalize", false) => "слова с большой буквы",
+            ("add_number", true) => "add a number",
+            ("add_number", false) => "добавить цифру",
+            ("pin_len", true) => "PIN length",
+            ("pin_len", false) => "Длина PIN-кода",
+            ("pin_no_patterns", true) => "no repeats or sequences",
+            ("pin_no_patterns", false) => "без повторов и серий",
+            ("token_bytes", true) => "Random bytes",
+            ("token_bytes", false) => "Случайных байт",
+            ("token_encoding", true) => "encoding",
+            ("token_encoding", false) => "кодировка",
+            ("id_kind", true) => "format",
+            ("id_kind", false) => "формат",
+            ("analyze_input", true) => "Password to analyze",
+            ("analyze_input", false) => "Пароль для анализа",
+            _ => field,
+        };
+        label.to_string()
+    }
+
+    fn field_value<'a>(generator: &'a Generator, field: &str) -> Option<&'a str> {
+        match field {
+            "pwd_len" => Some(&generator.pwd_len),
+            "words" => Some(&generator.words),
+            "pin_len" => Some(&generator.pin_len),
+            "token_bytes" => Some(&generator.token_bytes),
+            _ => None,
+        }
+    }
+
+    fn choice_value<'a>(generator: &'a Generator, field: &str) -> Option<&'a str> {
+        match field {
+            "separator" => Some(match generator.separator.as_str() {
+                " " => "space",
+                separator => separator,
+            }),
+            "token_encoding" => Some(&generator.token_encoding),
+            "id_kind" => Some(&generator.id_kind),
+            _ => None,
+        }
+    }
+
+    fn range_title(name: &str, range: (u32, u32)) -> String {
+        format!("{} ({} - {})", name, range.0, range.1)
+    }
+
+    fn tab_title(tab: &str, lang: &str) -> &'static str {
+        match (tab, lang == "en") {
+            ("passphrase", true) => "Passphrase",
+            ("passphrase", false) => "Фраза",
+            ("pin", _) => "PIN",
+            ("token", true) => "Token",
+            ("token", false) => "Токен",
+            ("identifier", true) => "Identifier",
+            ("identifier", false) => "Идентификатор",
+            ("analyze", true) => "Analyze",
+            ("analyze", false) => "Анализ",
+            (_, true) => "Password",
+            (_, false) => "Пароль",
+        }
+    }
+
+    fn draw_presets_popup(f: &mut Frame, generator: &Generator) {
+        let theme = current_theme(generator);
+        let en = generator.lang.as_str() == "en";
+        let mut lines: Vec<Line> = Vec::new();
+        for (idx, preset) in generator.presets.iter().enumerate() {
+            let mut classes: Vec<&str> = Vec::new();
+            if preset.strong {
+                classes.push(if en { "strong" } else { "сильный" });
+            } else {
+                if preset.lowercase {
+                    classes.push("a-z");
+                }
+                if preset.uppercase {
+                    classes.push("A-Z");
+                }
+                if preset.digits {
+                    classes.push("0-9");
+                }
+                if preset.symbols {
+                    classes.push("#$%");
+                }
+            }
+            let marker = if preset.name == generator.active_preset {
+                "*"
             } else {
-                pwd_len_field.white()
+                " "
             };
-            f.render_widget(pwd_len_field, pwd_len_field_area);
+            let line = Line::from(format!(
+                "{} {:<24} {:>5}  {}",
+                marker,
+                preset.name,
+                preset.length,
+                classes.join(" ")
+            ));
+            lines.push(if idx == generator.presets_position {
+                line.style(theme.focus).bold()
+            } else {
+                line
+            });
+        }
+        let save_row = match &generator.preset_name {
+            Some(name) => Line::from(format!("+ {}: {}", if en { "name" } else { "имя" }, name)),
+            None => Line::from(if en {
+                "+ save current rules as a preset"
+            } else {
+                "+ сохранить текущие правила как набор"
+            }),
+        };
+        lines.push(if generator.presets_position == generator.presets.len() {
+            save_row.style(theme.focus).bold()
+        } else {
+            save_row
+        });
+        lines.push(Line::from(""));
+        lines.push(
+            Line::from(if generator.preset_name.is_some() {
+                if en {
+                    "Enter save, Esc cancel"
+                } else {
+                    "Enter сохранить, Esc отмена"
+                }
+            } else if en {
+                "▲▼ select, Enter apply, Delete remove, Esc close"
+            } else {
+                "▲▼ выбор, Enter применить, Delete удалить, Esc закрыть"
+            })
+            .italic(),
+        );
+
+        let height = (lines.len() as u16 + 2).min(f.area().height);
+        let area = centered_rect(f.area(), 60.min(f.area().width), height);
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(lines)
+                .block(
+                    Block::default()
+                        .title(if en {
+                            "Presets"
+                        } else {
+                            "Наборы правил"
+                        })
+                        .title_alignment(Center)
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded),
+                )
+                .style(theme.base),
+            area,
+        );
+        if let Some(name) = &generator.preset_name {
+            let label_len = if en { 8 } else { 7 };
             f.set_cursor_position(Position::from((
-                pwd_len_field_area.x + generator.cursor_position as u16 + 1,
-                pwd_len_field_area.y + 1,
+                area.x + 1 + label_len + name.chars().count() as u16,
+                area.y + 1 + generator.presets.len() as u16,
             )));
+        }
+    }
 
-            // Rules
-            let fields = if generator.lang.as_str() == "en" {
-                [
-                    ("letters", "include lowercase letters", 14),
-                    ("u_letters", "include capital letters", 17),
-                    ("numbs", "include numbers", 20),
-                    ("spec_symbs", "include special symbols", 23),
-                    ("convenience_criterion", "strong & usability password", 26),
-                ]
+    fn draw_quit_popup(f: &mut Frame, generator: &Generator) {
+        let theme = current_theme(generator);
+        let en = generator.lang.as_str() == "en";
+        let lines = vec![
+            Line::from(if en {
+                "Exit the generator?"
             } else {
-                [
-                    ("letters", "включая маленькие буквы", 14),
-                    ("u_letters", "включая большие буквы", 17),
-                    ("numbs", "включая цифры", 20),
-                    ("spec_symbs", "включая спец. символы", 23),
-                    ("convenience_criterion", "сильный и удобный пароль", 26),
-                ]
-            };
+                "Выйти из генератора?"
+            })
+            .bold(),
+            Line::from(""),
+            Line::from(if en {
+                "y, Enter exit, Esc stay"
+            } else {
+                "y, Enter выйти, Esc остаться"
+            })
+            .italic(),
+        ];
+        let area = centered_rect(f.area(), 34.min(f.area().width), 5.min(f.area().height));
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(lines)
+                .alignment(Alignment::Center)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded),
+                )
+                .style(theme.base),
+            area,
+        );
+    }
 
-            for field in fields {
-                let on_criteria = if generator.get_rule_state(field.0) {
-                    "+"
+    fn draw_escape_popup(f: &mut Frame, generator: &Generator) {
+        let theme = current_theme(generator);
+        let en = generator.lang.as_str() == "en";
+        let hidden = generator.pwd_masked || generator.focus_lost;
+        // Wiped once drawn, the lines below only borrow them
+        let mut escaped: Vec<String> = ESCAPE_VIEWS
+            .iter()
+            .map(|view| {
+                if hidden {
+                    "•".repeat(generator.pwd.chars().count().min(50))
                 } else {
-                    "-"
-                };
-                let fi_area = centered_rect(Rect::new(0, field.2, f.area().width, 3), 44, 3);
-                let mut field_par = Paragraph::new(format!(" {}   {}", on_criteria, field.1))
-                    .block(Block::default().borders(Borders::ALL));
-                field_par = if generator.rules_position == field.0 {
-                    field_par.yellow()
-                } else {
-                    field_par.white()
-                };
-                f.render_widget(field_par, fi_area);
+                    escape_for(view, &generator.pwd)
+                }
+            })
+            .collect();
+        let mut lines: Vec<Line> = Vec::new();
+        for (idx, view) in ESCAPE_VIEWS.iter().enumerate() {
+            let text = &escaped[idx];
+            let mut spans = vec![Span::raw(format!("{:<12}", view_title(view))).bold()];
+            if text.chars().count() > 50 {
+                spans.push(Span::raw(char_prefix(text, 47)));
+                spans.push(Span::raw("..."));
+            } else {
+                spans.push(Span::raw(text.as_str()));
             }
+            let line = Line::from(spans);
+            lines.push(if idx == generator.escape_position {
+                line.style(theme.focus)
+            } else {
+                line
+            });
+        }
+        lines.push(Line::from(""));
+        lines.push(
+            Line::from(if en {
+                "▲▼ select, Enter copy, Esc close"
+            } else {
+                "▲▼ выбор, Enter копировать, Esc закрыть"
+            })
+            .italic(),
+        );
+        if !generator.errors.0.is_empty() {
+            lines.push(Line::from(if en {
+                generator.errors.0.clone()
+            } else {
+                generator.errors.1.clone()
+            }));
+        } else if !generator.notice.0.is_empty() {
+            lines.push(Line::from(if en {
+                generator.notice.0.clone()
+            } else {
+                generator.notice.1.clone()
+            }));
+        }
 
-            // Password result area
-            let mut pwd = generator.pwd.clone();
-            if pwd.len() > 40 {
-                pwd = format!("{}...", &pwd[..39].to_string());
-            }
-            if generator.pwd != "" {
-                let text: String = if generator.lang.as_str() == "en" {
-                    if generator.errors.0 != "" {
-                        generator.errors.0.clone()
-                    } else {
-                        "this password was copied to clipboard".to_string()
-                    }
+        let height = (lines.len() as u16 + 2).min(f.area().height);
+        let area = centered_rect(f.area(), 66.min(f.area().width), height);
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(lines)
+                .block(
+                    Block::default()
+                        .title(if en {
+                            "Escaped password"
+                        } else {
+                            "Экранированный пароль"
+                        })
+                        .title_alignment(Center)
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded),
+                )
+                .style(theme.base),
+            area,
+        );
+        for text in escaped.iter_mut() {
+            wipe_secret(text);
+        }
+    }
+
+    /// Every keybinding and rule with a short explanation, in the current language.
+    fn draw_help_popup(f: &mut Frame, generator: &mut Generator) {
+        let theme = current_theme(generator);
+        let en = generator.lang.as_str() == "en";
+        let keys: [(&[Action], &str, &str); 24] = [
+            (
+                &[Action::Help],
+                "open or close this help",
+                "открыть или закрыть справку",
+            ),
+            (&[Action::Quit], "exit", "выход"),
+            (
+                &[Action::Lang],
+                "switch interface language",
+                "переключить язык интерфейса",
+            ),
+            (
+                &[Action::PrevTab, Action::NextTab],
+                "switch tabs",
+                "переключить вкладку",
+            ),
+            (
+                &[Action::NextField, Action::Down],
+                "next field",
+                "следующее поле",
+            ),
+            (
+                &[Action::PrevField, Action::Up],
+                "previous field",
+                "предыдущее поле",
+            ),
+            (
+                &[Action::Up, Action::Down, Action::Increase, Action::Decrease],
+                "change the focused number by 1",
+                "изменить число в поле на 1",
+            ),
+            (
+                &[Action::StepUp, Action::StepDown],
+                "change the number by 8",
+                "изменить число на 8",
+            ),
+            (
+                &[
+                    Action::CommonLength(0),
+                    Action::CommonLength(1),
+                    Action::CommonLength(2),
+                    Action::CommonLength(3),
+                    Action::CommonLength(4),
+                ],
+                "length 12, 16, 20, 32 or 64",
+                "длина 12, 16, 20, 32 или 64",
+            ),
+            (
+                &[Action::Toggle],
+                "toggle a rule or switch a choice",
+                "включить правило или сменить вариант",
+            ),
+            (&[], "type a number into the field", "ввести число в поле"),
+            (
+                &[Action::CursorLeft, Action::CursorRight],
+                "move the cursor in the field",
+                "двигать курсор в поле",
+            ),
+            (
+                &[Action::CursorHome, Action::CursorEnd],
+                "cursor to the start or the end",
+                "курсор в начало или в конец",
+            ),
+            (
+                &[Action::Backspace],
+                "erase before the cursor",
+                "стереть перед курсором",
+            ),
+            (
+                &[Action::Delete],
+                "erase under the cursor",
+                "стереть под курсором",
+            ),
+            (
+                &[Action::Generate, Action::Regenerate],
+                "generate (Enter accepts in --pick)",
+                "сгенерировать (Enter выбирает в --pick)",
+            ),
+            (&[Action::Copy], "copy the result", "скопировать результат"),
+            (
+                &[Action::ClipboardTarget],
+                "choose the clipboard",
+                "выбрать буфер обмена",
+            ),
+            (
+                &[Action::AutoCopy],
+                "switch copying on generation",
+                "копирование при генерации",
+            ),
+            (
+                &[Action::Presets],
+                "presets of password rules",
+                "наборы правил пароля",
+            ),
+            (
+                &[Action::Escape],
+                "copies escaped for shell, JSON...",
+                "копии с экранированием для shell, JSON...",
+            ),
+            (
+                &[Action::Mask],
+                "show or hide the password",
+                "показать или скрыть пароль",
+            ),
+            (&[Action::History], "session history", "история сессии"),
+            (
+                &[Action::Viewer],
+                "view a long password in full",
+                "весь длинный пароль целиком",
+            ),
+        ];
+        let keys: Vec<(String, &str, &str)> = keys
+            .iter()
+            .filter_map(|(actions, en_text, ru_text)| {
+                let names = if actions.is_empty() {
+                    vec!["0-9".to_string()]
                 } else {
-                    if generator.errors.1 != "" {
-                        generator.errors.1.clone()
-                    } else {
-                        "пароль был скопирован в буфер обмена".to_string()
+                    generator.keymap.key_names(actions)
+                };
+                let key = match (actions.first(), names.first(), names.last()) {
+                    // Alt+1 .. Alt+5 in one short range
+                    (Some(Action::CommonLength(_)), Some(first), Some(last)) if names.len() > 2 => {
+                        format!("{}..{}", first, last)
                     }
+                    _ => names.join(", "),
                 };
-                let pwd_text = vec![
-                    Line::from(Span::raw("")),
-                    Line::from(Span::raw(pwd).bold()),
-                    Line::from(Span::raw("")),
-                    Line::from(Span::raw(text).italic().on_gray()),
-                ];
-                let pwd_title = if generator.lang.as_str() == "en" {
-                    "Password is"
+                (!names.is_empty()).then_some((key, *en_text, *ru_text))
+            })
+            .collect();
+        let key_width = keys
+            .iter()
+            .map(|(key, _, _)| key.width())
+            .max()
+            .unwrap_or(0)
+            .min(17)
+            + 1;
+        let rules: [(&str, &str); 5] = if en {
+            [
+                ("lowercase letters", "a-z"),
+                ("capital letters", "A-Z"),
+                ("numbers", "0-9"),
+                ("special symbols", ")([]{}*&^%$#@!~"),
+                (
+                    "strong & usability",
+                    "ignores the toggles above: letters and digits without look-alikes (I l O 0 1...), starts with a letter and ends with one of *&%$#@!. Also used when no class is on.",
+                ),
+            ]
+        } else {
+            [
+                ("маленькие буквы", "a-z"),
+                ("большие буквы", "A-Z"),
+                ("цифры", "0-9"),
+                ("спец. символы", ")([]{}*&^%$#@!~"),
+                (
+                    "сильный и удобный",
+                    "игнорирует правила выше: буквы и цифры без похожих (I l O 0 1...), начинается с буквы и заканчивается одним из *&%$#@!. Также используется, если ни один класс не включён.",
+                ),
+            ]
+        };
+
+        let mut lines: Vec<Line> = vec![Line::from(if en { "Keys" } else { "Клавиши" }).bold()];
+        for (key, en_text, ru_text) in keys {
+            lines.push(Line::from(vec![
+                Span::raw(format!(
+                    "{}{}",
+                    key,
+                    " ".repeat(key_width.saturating_sub(key.width()).max(1))
+                ))
+                .bold(),
+                Span::raw(if en { en_text } else { ru_text }),
+            ]));
+        }
+        lines.push(Line::from(""));
+        lines.push(
+            Line::from(if en {
+                "Password rules"
+            } else {
+                "Правила пароля"
+            })
+            .bold(),
+        );
+        for (rule, charset) in rules {
+            lines.push(Line::from(vec![
+                Span::raw(format!("{}: ", rule)).bold(),
+                Span::raw(charset),
+            ]));
+        }
+        lines.push(Line::from(""));
+        lines.push(
+            Line::from(if en {
+                "▲▼ PgUp PgDn scroll, Esc close"
+            } else {
+                "▲▼ PgUp PgDn прокрутка, Esc закрыть"
+            })
+            .italic(),
+        );
+
+        let area = centered_rect(f.area(), 66.min(f.area().width), 38.min(f.area().height));
+        let help = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(
+                Block::default()
+                    .title(if en { "Help" } else { "Справка" })
+                    .title_alignment(Center)
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            )
+            .style(theme.base);
+        // Wrapped rows count too, narrow terminals wrap most of the help
+        let rows = help.line_count(area.width.saturating_sub(2));
+        let max_scroll =
+            u16::try_from(rows.saturating_sub(area.height as usize)).unwrap_or(u16::MAX);
+        // Keeps ▲ responsive after a long scroll past the bottom
+        generator.help_scroll = generator.help_scroll.min(max_scroll);
+        f.render_widget(Clear, area);
+        f.render_widget(help.scroll((generator.help_scroll, 0)), area);
+    }
+
+    /// The whole password in rows of tens, under a ruler and with the position of each row.
+    fn draw_viewer_popup(f: &mut Frame, generator: &mut Generator) {
+        let theme = current_theme(generator);
+        let en = generator.lang.as_str() == "en";
+        let width = 84.min(f.area().width);
+        // 6 columns go to the position, rows are whole tens when there is room
+        let text_width = width.saturating_sub(8).max(1) as usize;
+        let row_len = if text_width >= 10 {
+            text_width / 10 * 10
+        } else {
+            text_width
+        };
+        let pwd_len = generator.pwd.chars().count();
+        let row_count = pwd_len.div_ceil(row_len);
+        let height = (row_count as u16 + 5).min(f.area().height);
+        let area = centered_rect(f.area(), width, height);
+        let visible = height.saturating_sub(5) as usize;
+        let scroll = (generator.viewer_scroll as usize).min(row_count.saturating_sub(visible));
+        // Keeps ▲ responsive after End or a long scroll past the bottom
+        generator.viewer_scroll = scroll as u16;
+        let last = ((scroll + visible) * row_len).min(pwd_len);
+        let hidden = generator.pwd_masked || generator.focus_lost;
+        // Rows are slices of the password, not copies of it
+        let starts: Vec<usize> = generator
+            .pwd
+            .char_indices()
+            .map(|(idx, _)| idx)
+            .step_by(row_len)
+            .chain([generator.pwd.len()])
+            .collect();
+        let rows = starts.windows(2).enumerate().map(|(idx, bounds)| {
+            let row = &generator.pwd[bounds[0]..bounds[1]];
+            Line::from(vec![
+                Span::raw(format!("{:>5} ", idx * row_len + 1)),
+                if hidden {
+                    Span::raw("•".repeat(row.chars().count()))
                 } else {
-                    "Ваш пароль"
-                };
-                let par = Paragraph::new(pwd_text)
-                    .block(
-                        Block::new()
-                            .title(pwd_title)
-                            .title_alignment(Center)
-                            .borders(Borders::ALL)
-                            .border_type(BorderType::Rounded)
-                            .black(),
-                    )
-                    .black()
-                    .on_white()
-                    .alignment(Alignment::Center);
-                f.render_widget(
-                    par,
-                    centered_rect(Rect::new(0, 30, f.area().width, 6), 55, 6),
-                );
-            }
+                    Span::raw(row)
+                },
+            ])
+        });
 
-            // Footer
-            let ver = env!("CARGO_PKG_VERSION");
-            let footer = if generator.lang.as_str() == "en" {
-                format!("Dev on Rust | v{ver} | 2024 | https://passgen.mamont.xyz")
+        let ruler: String = (1..=row_len)
+            .map(|col| match col % 10 {
+                0 => char::from(b'0' + (col / 10 % 10) as u8),
+                5 => '+',
+                _ => '.',
+            })
+            .collect();
+        let mut lines = vec![Line::from(format!("      {}", ruler)).style(theme.muted)];
+        lines.extend(rows.skip(scroll).take(visible));
+        lines.push(Line::from(""));
+        lines.push(
+            Line::from(if en {
+                "▲▼ PgUp PgDn scroll, F8 show/hide, F2 copy, Esc close"
             } else {
-                format!("Создано на языке Rust | v{ver} | 2024 | https://passgen.mamont.xyz")
+                "▲▼ PgUp PgDn прокрутка, F8 показать/скрыть, F2 копировать, Esc закрыть"
+            })
+            .italic(),
+        );
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(lines)
+                .block(
+                    Block::default()
+                        .title(if en {
+                            format!(
+                                "Password, {}-{} of {}",
+                                (scroll * row_len + 1).min(last),
+                                last,
+                                pwd_len
+                            )
+                        } else {
+                            format!(
+                                "Пароль, {}-{} из {}",
+                                (scroll * row_len + 1).min(last),
+                                last,
+                                pwd_len
+                            )
+                        })
+                        .title_alignment(Center)
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded),
+                )
+                .style(theme.base),
+            area,
+        );
+    }
+
+    /// Session history docked to the right edge of the terminal.
+    fn draw_history_panel(f: &mut Frame, generator: &Generator) {
+        let theme = current_theme(generator);
+        let en = generator.lang.as_str() == "en";
+        let width = 40.min(f.area().width);
+        let height = 40.min(f.area().height);
+        let area = Rect::new(f.area().width - width, 0, width, height);
+        let text_width = width.saturating_sub(4) as usize;
+
+        let mut lines: Vec<Line> = Vec::new();
+        if generator.history.is_empty() {
+            lines.push(Line::from(if en {
+                "nothing generated yet"
+            } else {
+                "ещё ничего не сгенерировано"
+            }));
+        }
+        // Two lines per entry, the footer takes five more
+        let visible = (height.saturating_sub(7) / 2).max(1) as usize;
+        let first = generator.history_position.saturating_sub(visible - 1);
+        for (idx, entry) in generator
+            .history
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(visible)
+        {
+            let label = match (&generator.history_label, idx == generator.history_position) {
+                (Some(typed), true) => format!("{}: {}", if en { "label" } else { "метка" }, typed),
+                _ => entry.label.clone(),
             };
-            let par = Paragraph::new(footer)
-                .block(Block::default().borders(Borders::TOP).gray().on_black())
-                .cyan()
-                .on_black()
-                .alignment(Alignment::Center);
-            f.render_widget(
-                par,
-                centered_rect(Rect::new(0, 37, f.area().width, 2), 68, 2),
+            let head = Line::from(format!(
+                "{} {:<10} {}",
+                entry.time,
+                tab_title(&entry.tab, &generator.lang),
+                label
+            ));
+            let revealed = entry.revealed && !generator.focus_lost;
+            let body = if revealed && entry.secret.chars().count() > text_width {
+                Line::from(vec![
+                    Span::raw("  "),
+                    Span::raw(char_prefix(&entry.secret, text_width.saturating_sub(3))),
+                    Span::raw("..."),
+                ])
+            } else if revealed {
+                Line::from(vec![Span::raw("  "), Span::raw(entry.secret.as_str())])
+            } else {
+                Line::from(format!(
+                    "  {}",
+                    "•".repeat(entry.secret.chars().count().min(text_width))
+                ))
+            };
+            if idx == generator.history_position {
+                lines.push(head.style(theme.focus).bold());
+                lines.push(body.style(theme.focus));
+            } else {
+                lines.push(head);
+                lines.push(body.style(theme.muted));
+            }
+        }
+        lines.push(Line::from(""));
+        if generator.history_label.is_some() {
+            lines.push(
+                Line::from(if en {
+                    "Enter save, Esc cancel"
+                } else {
+                    "Enter сохранить, Esc отмена"
+                })
+                .italic(),
             );
+        } else {
+            lines.push(
+                Line::from(if en {
+                    "▲▼ select, Space show/hide, Enter copy"
+                } else {
+                    "▲▼ выбор, Space показать, Enter копия"
+                })
+                .italic(),
+            );
+            lines.push(
+                Line::from(if en {
+                    "l label, Delete remove, Esc close"
+                } else {
+                    "l метка, Delete удалить, Esc закрыть"
+                })
+                .italic(),
+            );
+        }
+        if !generator.errors.0.is_empty() {
+            lines.push(Line::from(if en {
+                generator.errors.0.clone()
+            } else {
+                generator.errors.1.clone()
+            }));
+        } else if !generator.notice.0.is_empty() {
+            lines.push(Line::from(if en {
+                generator.notice.0.clone()
+            } else {
+                generator.notice.1.clone()
+            }));
+        }
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(lines)
+                .block(
+                    Block::default()
+                        .title(if en {
+                            "History (UTC)"
+                        } else {
+                            "История (UTC)"
+                        })
+                        .title_alignment(Center)
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded),
+                )
+                .style(theme.base),
+            area,
+        );
+    }
+
+    /// `NO_COLOR` falls back to the monochrome theme unless a theme was set explicitly.
+    fn current_theme(generator: &Generator) -> Theme {
+        match &generator.theme {
+            Some(theme) => theme_for(theme),
+            None if generator.no_color => theme_for("mono"),
+            None => theme_for("dark"),
+        }
+    }
+
+    /// The first `chars` characters of `text`, borrowed.
+    fn char_prefix(text: &str, chars: usize) -> &str {
+        match text.char_indices().nth(chars) {
+            Some((idx, _)) => &text[..idx],
+            None => text,
+        }
+    }
+
+    fn notice_suffix(notice: &str) -> String {
+        if notice.is_empty() {
+            "".to_string()
+        } else {
+            format!(" | {}", notice)
+        }
+    }
+
+    fn clipboard_target_name(generator: &Generator) -> &'static str {
+        match (generator.clipboard_target.as_str(), generator.lang.as_str()) {
+            ("primary", _) => "PRIMARY",
+            ("both", _) => "CLIPBOARD + PRIMARY",
+            ("none", "en") => "nowhere",
+            ("none", _) => "никуда",
+            _ => "CLIPBOARD",
         }
     }
 